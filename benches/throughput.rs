@@ -0,0 +1,224 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serialmessage::{Crc, Crc8, FrameBuilder, FramingConfig, SerMsg};
+
+// The byte-at-a-time parser before the incremental CRC and COBS decoding, kept as the baseline
+// of the parse benchmarks. It zero-fills the payload on every start byte, calculates the CRC
+// once the CRC byte arrived and walks the COBS chain after the stop byte.
+mod baseline {
+    use super::{Crc, Crc8, FramingConfig};
+
+    const START_BYTE: u8 = FramingConfig::DEFAULT.start_byte();
+    const STOP_BYTE: u8 = FramingConfig::DEFAULT.stop_byte();
+
+    enum FindByte {
+        Start,
+        Id,
+        Overhead,
+        PayloadLen,
+        Payload,
+        Crc,
+        End,
+    }
+
+    pub struct Parser {
+        msg_state: FindByte,
+        rcvd_data: [u8; 254],
+        rcvd_ind: usize,
+        rcvd_id: u8,
+        cobs_byte: u8,
+        payload_len: u8,
+        rcvd_crc: u8,
+        crc: Crc8,
+    }
+
+    impl Parser {
+        pub fn new() -> Parser {
+            Parser {
+                msg_state: FindByte::Start,
+                rcvd_data: [0; 254],
+                rcvd_ind: 0,
+                rcvd_id: 0,
+                cobs_byte: 0,
+                payload_len: 0,
+                rcvd_crc: 0,
+                crc: Crc8::new(),
+            }
+        }
+
+        // Returns the id and the payload of a completed message
+        pub fn parse_byte(&mut self, val: u8) -> Option<(u8, &[u8])> {
+            match self.msg_state {
+                FindByte::Start => {
+                    if val == START_BYTE {
+                        self.rcvd_data.fill(0);
+                        self.rcvd_ind = 0;
+                        self.msg_state = FindByte::Id;
+                    }
+                }
+                FindByte::Id => {
+                    self.rcvd_id = val;
+                    self.msg_state = FindByte::Overhead;
+                }
+                FindByte::Overhead => {
+                    self.cobs_byte = val;
+                    self.msg_state = FindByte::PayloadLen;
+                }
+                FindByte::PayloadLen => {
+                    self.payload_len = val;
+                    self.msg_state = match val {
+                        255 => FindByte::Start,
+                        0 => FindByte::Crc,
+                        _ => FindByte::Payload,
+                    };
+                }
+                FindByte::Payload => {
+                    self.rcvd_data[self.rcvd_ind] = val;
+                    self.rcvd_ind += 1;
+                    if self.rcvd_ind == self.payload_len as usize {
+                        self.msg_state = FindByte::Crc;
+                    }
+                }
+                FindByte::Crc => {
+                    self.rcvd_crc = val;
+                    let calc_crc = self.crc.checksum(&self.rcvd_data[..self.rcvd_ind]);
+                    self.msg_state = match calc_crc == self.rcvd_crc as u32 {
+                        true => FindByte::End,
+                        false => FindByte::Start,
+                    };
+                }
+                FindByte::End => {
+                    self.msg_state = FindByte::Start;
+                    let payload = &mut self.rcvd_data[..self.rcvd_ind];
+                    if val == STOP_BYTE && unpack_cobs(self.cobs_byte, payload) {
+                        return Some((self.rcvd_id, payload));
+                    }
+                }
+            }
+            None
+        }
+    }
+
+    fn unpack_cobs(mut cobs_byte: u8, data: &mut [u8]) -> bool {
+        if cobs_byte == 0xFF {
+            return true;
+        }
+        loop {
+            let Some(&delta) = data.get(cobs_byte as usize) else {
+                return false;
+            };
+            data[cobs_byte as usize] = START_BYTE;
+            match delta {
+                0 => return true,
+                delta => cobs_byte = cobs_byte.saturating_add(delta),
+            }
+        }
+    }
+}
+
+// Payloads without start bytes and with a start byte every 8 bytes
+fn payloads() -> Vec<(&'static str, Vec<u8>)> {
+    let mut payloads = Vec::new();
+    for len in [16, 254] {
+        let plain: Vec<u8> = (0..len).map(|ind| (ind % 100) as u8).collect();
+        let stuffed: Vec<u8> = (0..len)
+            .map(|ind| if ind % 8 == 0 { 126 } else { ind as u8 })
+            .collect();
+        payloads.push(("plain", plain));
+        payloads.push(("start_bytes", stuffed));
+    }
+    payloads
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, payload) in payloads() {
+        let mut rcvd_bytes = Vec::new();
+        for id in 0..16 {
+            SerMsg::encode_extend(&payload, id, &mut rcvd_bytes).unwrap();
+        }
+        // The baseline has to do the same work as the parser to be comparable
+        let mut parser = baseline::Parser::new();
+        let baseline_frames: Vec<(u8, Vec<u8>)> = rcvd_bytes
+            .iter()
+            .filter_map(|val| {
+                parser
+                    .parse_byte(*val)
+                    .map(|(id, data)| (id, data.to_vec()))
+            })
+            .collect();
+        let mut ser_msg = SerMsg::new();
+        let mut frames = Vec::new();
+        let mut bytes = &rcvd_bytes[..];
+        while !bytes.is_empty() {
+            if let Some(frame) = ser_msg.parse(&mut bytes).unwrap() {
+                frames.push((frame.id, frame.payload.to_vec()));
+            }
+        }
+        assert_eq!(baseline_frames.len(), 16);
+        assert_eq!(baseline_frames, frames);
+
+        group.throughput(Throughput::Bytes(rcvd_bytes.len() as u64));
+        let id = BenchmarkId::new(name, payload.len());
+        group.bench_with_input(id, &rcvd_bytes, |b, rcvd_bytes| {
+            let mut ser_msg = SerMsg::new();
+            b.iter(|| {
+                let mut bytes = &rcvd_bytes[..];
+                while !bytes.is_empty() {
+                    if let Ok(Some(frame)) = ser_msg.parse(&mut bytes) {
+                        black_box(frame);
+                    }
+                }
+            })
+        });
+
+        let id = BenchmarkId::new(format!("baseline/{}", name), payload.len());
+        group.bench_with_input(id, &rcvd_bytes, |b, rcvd_bytes| {
+            let mut parser = baseline::Parser::new();
+            b.iter(|| {
+                for val in rcvd_bytes.iter() {
+                    if let Some(frame) = parser.parse_byte(*val) {
+                        black_box(frame);
+                    }
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode");
+    for (name, payload) in payloads() {
+        let msg_len = SerMsg::encoded_len(payload.len());
+        group.throughput(Throughput::Bytes(msg_len as u64));
+
+        let id = BenchmarkId::new(format!("into/{}", name), payload.len());
+        group.bench_with_input(id, &payload, |b, payload| {
+            let mut tx_buffer = [0; 260];
+            b.iter(|| SerMsg::encode_into(black_box(payload), 1, &mut tx_buffer).unwrap())
+        });
+
+        let id = BenchmarkId::new(format!("iter/{}", name), payload.len());
+        group.bench_with_input(id, &payload, |b, payload| {
+            let parts = [&payload[..]];
+            b.iter(|| {
+                SerMsg::encode_slices_iter(black_box(&parts), 1)
+                    .unwrap()
+                    .fold(0_u8, |acc, val| acc ^ val)
+            })
+        });
+
+        let id = BenchmarkId::new(format!("builder/{}", name), payload.len());
+        group.bench_with_input(id, &payload, |b, payload| {
+            let mut builder = FrameBuilder::new();
+            b.iter(|| {
+                builder.put_bytes(black_box(payload)).unwrap();
+                black_box(builder.finish(1));
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, encode);
+criterion_main!(benches);
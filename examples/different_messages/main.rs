@@ -1,7 +1,7 @@
 extern crate serialmessage;
 extern crate serialport;
 
-use serialmessage::{ParseState, SerMsg};
+use serialmessage::SerMsg;
 use std::{env, thread, time::Duration};
 use zerocopy::{AsBytes, FromBytes};
 
@@ -57,27 +57,21 @@ fn main() {
     let mut buffer = [0; 256];
     let read_amount = port.read(&mut buffer).unwrap();
     let read_bytes = buffer[..read_amount].to_owned();
-    let mut all_parsed_bytes = 0;
 
-    while read_amount > all_parsed_bytes {
-        let (state, parsed_bytes) = ser_msg.parse_read_bytes(&read_bytes[all_parsed_bytes..]);
-        all_parsed_bytes += parsed_bytes;
-
-        match state {
-            ParseState::DataReady => {
-                if ser_msg.return_msg_id() == 1 {
-                    let rcvd_two_nums = TwoNumbers::read_from(ser_msg.return_read_data()).unwrap();
-                    println!("Rcvd: {:?}", rcvd_two_nums);
-                } else if ser_msg.return_msg_id() == 2 {
-                    let rcvd_one_num = OneNumber::read_from(ser_msg.return_read_data()).unwrap();
-                    println!("Rcvd: {:?}", rcvd_one_num);
-                } else {
-                    println!("Invalid ID: {}", ser_msg.return_msg_id());
-                }
-            }
-            _ => {
-                println!("State: {:?}", state);
+    ser_msg.for_each_frame(&read_bytes, |frame| match frame {
+        Ok(frame) => {
+            if frame.id == 1 {
+                let rcvd_two_nums = TwoNumbers::read_from(frame.payload).unwrap();
+                println!("Rcvd: {:?}", rcvd_two_nums);
+            } else if frame.id == 2 {
+                let rcvd_one_num = OneNumber::read_from(frame.payload).unwrap();
+                println!("Rcvd: {:?}", rcvd_one_num);
+            } else {
+                println!("Invalid ID: {}", frame.id);
             }
         }
-    }
+        Err(e) => {
+            println!("Error: {:?}", e);
+        }
+    });
 }
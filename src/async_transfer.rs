@@ -0,0 +1,158 @@
+//! Sending and receiving messages over the asynchronous traits of `embedded-io-async`, e.g. with embassy.
+
+use embedded_io_async::{Read, Write};
+
+use crate::chunks::{next_chunk, RxChunks, CHUNK_LEN};
+use crate::{FrameRef, FramingConfig, Message, SerMsg, TickClock, TransferError};
+
+/// Sends and receives messages over a port implementing the traits of `embedded-io-async`,
+/// the asynchronous counterpart of `EmbeddedTransfer`.
+///
+/// The parser and the chunk read but not parsed yet are kept by the AsyncTransfer, not by the
+/// future returned by [AsyncTransfer.receive()][receive]. If the future is dropped, e.g. by a
+/// `select!` completing another branch, a partly received message is completed by the next
+/// call, as long as the `read()` of the port is cancel-safe.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{AsyncTransfer, SerMsg};
+///
+/// # futures::executor::block_on(async {
+/// // Stands in for the receiving half of a UART
+/// let rcvd_bytes = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+/// let mut rx = AsyncTransfer::new(&rcvd_bytes[..]);
+/// let msg = rx.receive().await.unwrap();
+/// assert_eq!((msg.id, &msg.payload[..]), (1, &[1, 2, 3][..]));
+///
+/// // Stands in for the sending half of a UART
+/// let mut tx_buffer = [0; 64];
+/// let mut tx = AsyncTransfer::new(&mut tx_buffer[..]);
+/// tx.send(&[1, 2, 3], 1).await.unwrap();
+/// assert_eq!(&tx_buffer[..rcvd_bytes.len()], &rcvd_bytes[..]);
+/// # });
+/// ```
+///
+/// [receive]: AsyncTransfer::receive()
+pub struct AsyncTransfer<U> {
+    port: U,
+    ser_msg: SerMsg,
+    rx_chunks: RxChunks<CHUNK_LEN>,
+}
+
+impl<U> AsyncTransfer<U> {
+    /// Returns a new AsyncTransfer awaiting the reads and writes of the port
+    pub fn new(port: U) -> AsyncTransfer<U> {
+        AsyncTransfer::with_config(port, FramingConfig::DEFAULT)
+    }
+
+    /// Returns a new AsyncTransfer awaiting the reads and writes of the port,
+    /// framing the messages with the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: FramingConfig
+    pub fn with_config(port: U, config: FramingConfig) -> AsyncTransfer<U> {
+        AsyncTransfer {
+            port,
+            ser_msg: SerMsg::with_config(config),
+            rx_chunks: RxChunks::new(),
+        }
+    }
+
+    /// Returns the port, e.g. to check its state between two awaited calls
+    pub fn port(&self) -> &U {
+        &self.port
+    }
+
+    /// Returns the port, e.g. to change the baud rate while no future of this
+    /// AsyncTransfer is alive
+    pub fn port_mut(&mut self) -> &mut U {
+        &mut self.port
+    }
+
+    /// Returns the port, dropping the parser state a cancelled
+    /// [AsyncTransfer.receive()][receive] left behind
+    ///
+    /// [receive]: AsyncTransfer::receive()
+    pub fn into_inner(self) -> U {
+        self.port
+    }
+
+    /// Returns the parser shared by all calls to [AsyncTransfer.receive()][receive],
+    /// e.g. to enable the resync before the first call
+    ///
+    /// [receive]: AsyncTransfer::receive()
+    pub fn parser_mut(&mut self) -> &mut SerMsg {
+        &mut self.ser_msg
+    }
+}
+
+impl<U: Read> AsyncTransfer<U> {
+    /// Waits for the next message and returns it.
+    /// Returns [Eof][Eof] if the port reached its end.
+    ///
+    /// Dropping the future doesn't lose any received bytes if the `read()` of the port is
+    /// cancel-safe, the next call continues with the parser state left behind.
+    ///
+    /// [Eof]: TransferError::Eof
+    pub async fn receive(&mut self) -> Result<Message, TransferError<U::Error>> {
+        while !self.rx_chunks.parse::<TickClock>(&mut self.ser_msg, None)? {
+            // The only await point. The chunk was parsed completely, so a read cancelled
+            // after writing into the buffer loses nothing.
+            let read_bytes = self
+                .port
+                .read(self.rx_chunks.buf_mut())
+                .await
+                .map_err(TransferError::Port)?;
+            if read_bytes == 0 {
+                return Err(TransferError::Eof);
+            }
+            self.rx_chunks.filled(read_bytes);
+        }
+        Ok(Message::from_parsed(FrameRef {
+            id: self.ser_msg.return_msg_id(),
+            payload: self.ser_msg.return_read_data(),
+        }))
+    }
+}
+
+impl<U: Write> AsyncTransfer<U> {
+    /// Packs the slice into the message format and writes the message to the port.
+    ///
+    /// Dropping the future can leave a partly written message on the port,
+    /// which the receiver drops as soon as the next message starts.
+    pub async fn send(&mut self, data: &[u8], id: u8) -> Result<(), TransferError<U::Error>> {
+        let parts = [data];
+        let mut msg = self.ser_msg.config().encode_slices_iter(&parts, id)?;
+        let mut chunk = [0; CHUNK_LEN];
+        loop {
+            let bytes = next_chunk(&mut chunk, &mut msg);
+            if bytes.is_empty() {
+                break;
+            }
+            self.port
+                .write_all(bytes)
+                .await
+                .map_err(TransferError::Port)?;
+        }
+        self.port.flush().await.map_err(TransferError::Port)
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="zerocopy")] {
+        use zerocopy::AsBytes;
+
+        impl<U: Write> AsyncTransfer<U> {
+            /// Awaits [AsyncTransfer.send()][send] with the bytes of the object as the payload
+            ///
+            /// [send]: AsyncTransfer::send()
+            pub async fn send_obj<O: AsBytes + ?Sized>(
+                &mut self,
+                obj: &O,
+                id: u8,
+            ) -> Result<(), TransferError<U::Error>> {
+                self.send(obj.as_bytes(), id).await
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Building messages in place, similar to `txObj()` and `sendData()` of the Arduino SerialTransfer library.
+
+use crate::crc::{self, Crc};
+use crate::{Crc8, Error, FramingConfig, SerMsg};
+
+/// Builds a message inside its own buffer without copying the payload.
+///
+/// The payload is written with [FrameBuilder.put_bytes()][put_bytes] or directly through
+/// [FrameBuilder.payload_mut()][payload_mut]. [FrameBuilder.finish()][finish] then packs the
+/// payload in place and returns the complete message.
+/// With the `zerocopy` feature, objects can be written with [FrameBuilder.put()][put] like
+/// with `txObj()` on the Arduino.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{FrameBuilder, SerMsg};
+///
+/// let mut builder = FrameBuilder::new();
+/// let next_ind = builder.put_bytes(&[1, 2]).unwrap();
+/// builder.put_bytes_at(next_ind, &[3]).unwrap();
+///
+/// let send_msg = builder.finish(1);
+/// assert_eq!(send_msg, &SerMsg::encode_vec(&[1, 2, 3], 1).unwrap()[..]);
+/// ```
+///
+/// [put_bytes]: FrameBuilder::put_bytes()
+/// [payload_mut]: FrameBuilder::payload_mut()
+/// [finish]: FrameBuilder::finish()
+/// [put]: FrameBuilder::put()
+pub struct FrameBuilder<C = Crc8> {
+    config: FramingConfig,
+    crc: C,
+    // The payload plus 5 bytes and a 4 byte CRC
+    msg: [u8; 263],
+    payload_len: usize,
+}
+
+impl Default for FrameBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameBuilder {
+    /// Returns a new FrameBuilder with an empty payload
+    pub fn new() -> FrameBuilder {
+        FrameBuilder::with_config(FramingConfig::DEFAULT)
+    }
+
+    /// Returns a new FrameBuilder with an empty payload creating messages
+    /// using the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: FramingConfig
+    pub fn with_config(config: FramingConfig) -> FrameBuilder {
+        FrameBuilder::with_crc(config, Crc8::new())
+    }
+}
+
+impl<C: Crc> FrameBuilder<C> {
+    /// Returns a new FrameBuilder with an empty payload creating messages
+    /// using the [FramingConfig][FramingConfig] and the [Crc][Crc]
+    ///
+    /// [FramingConfig]: FramingConfig
+    /// [Crc]: Crc
+    pub fn with_crc(config: FramingConfig, crc: C) -> FrameBuilder<C> {
+        FrameBuilder {
+            config,
+            crc,
+            msg: [0; 263],
+            payload_len: 0,
+        }
+    }
+
+    /// Returns the payload written so far
+    pub fn payload(&self) -> &[u8] {
+        &self.msg[4..4 + self.payload_len]
+    }
+
+    /// Returns the whole payload region of the maximum payload length. After writing to it directly,
+    /// the payload length has to be set with [FrameBuilder.set_len()][set_len].
+    ///
+    /// [set_len]: FrameBuilder::set_len()
+    pub fn payload_mut(&mut self) -> &mut [u8] {
+        &mut self.msg[4..4 + self.config.max_payload() as usize]
+    }
+
+    /// Returns the length of the payload
+    pub fn len(&self) -> usize {
+        self.payload_len
+    }
+
+    /// Returns true if the payload is empty
+    pub fn is_empty(&self) -> bool {
+        self.payload_len == 0
+    }
+
+    /// Sets the length of the payload. Returns [PayloadTooLarge][PayloadTooLarge]
+    /// if the length exceeds the maximum payload length.
+    ///
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    pub fn set_len(&mut self, payload_len: usize) -> Result<(), Error> {
+        if payload_len > self.config.max_payload() as usize {
+            return Err(Error::PayloadTooLarge { len: payload_len });
+        }
+        self.payload_len = payload_len;
+        Ok(())
+    }
+
+    /// Empties the payload
+    pub fn clear(&mut self) {
+        self.payload_len = 0;
+    }
+
+    /// Appends the bytes to the payload and returns the index following the written bytes.
+    /// Returns [PayloadTooLarge][PayloadTooLarge] if the bytes don't fit into the payload.
+    ///
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    pub fn put_bytes(&mut self, data: &[u8]) -> Result<usize, Error> {
+        self.put_bytes_at(self.payload_len, data)
+    }
+
+    /// Writes the bytes to the payload starting at `index` and returns the index following
+    /// the written bytes, like `txObj()` of the Arduino library. The payload grows if the bytes
+    /// are written past its end. Returns [PayloadTooLarge][PayloadTooLarge] if the bytes
+    /// don't fit into the payload.
+    ///
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    pub fn put_bytes_at(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
+        let next_ind = match index.checked_add(data.len()) {
+            Some(next_ind) if next_ind <= self.config.max_payload() as usize => next_ind,
+            // An end beyond usize::MAX is reported as usize::MAX
+            next_ind => {
+                return Err(Error::PayloadTooLarge {
+                    len: next_ind.unwrap_or(usize::MAX),
+                })
+            }
+        };
+        self.msg[4 + index..4 + next_ind].copy_from_slice(data);
+        self.payload_len = self.payload_len.max(next_ind);
+        Ok(next_ind)
+    }
+
+    /// Packs the payload in place into the message format and returns the complete message.
+    ///
+    /// The packing modifies the payload, so the builder is emptied afterwards and
+    /// the payload of the next message has to be written again.
+    pub fn finish(&mut self, id: u8) -> &[u8] {
+        let payload_len = self.payload_len;
+        self.payload_len = 0;
+        self.msg[0] = self.config.start_byte();
+        self.msg[1] = id;
+        self.msg[2] =
+            SerMsg::pack_cobs(self.config.start_byte(), &mut self.msg[4..4 + payload_len]);
+        self.msg[3] = payload_len as u8;
+        let calc_crc = self.crc.checksum(&self.msg[4..4 + payload_len]);
+        crc::write_crc::<C>(calc_crc, &mut self.msg[4 + payload_len..]);
+        let msg_len = SerMsg::encoded_len_crc::<C>(payload_len);
+        self.msg[msg_len - 1] = self.config.stop_byte();
+        &self.msg[..msg_len]
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="zerocopy")] {
+        use zerocopy::AsBytes;
+
+        impl<C: Crc> FrameBuilder<C> {
+            /// Appends the bytes of the object to the payload and returns the index following them.
+            /// Returns [PayloadTooLarge][PayloadTooLarge] if the object doesn't fit into the payload.
+            ///
+            /// # Example
+            ///
+            /// ```rust
+            /// use serialmessage::FrameBuilder;
+            /// use zerocopy::AsBytes;
+            ///
+            /// #[repr(C, packed)]
+            /// #[derive(AsBytes)]
+            /// struct Header {
+            ///     kind: u8,
+            ///     count: u16,
+            /// }
+            ///
+            /// let mut builder = FrameBuilder::new();
+            /// builder.put(&Header { kind: 1, count: 2 }).unwrap();
+            /// let next_ind = builder.put(&[1.5_f32, 2.5]).unwrap();
+            /// assert_eq!(next_ind, 11);
+            /// let send_msg = builder.finish(1);
+            /// ```
+            ///
+            /// [PayloadTooLarge]: Error::PayloadTooLarge
+            pub fn put<T: AsBytes + ?Sized>(&mut self, val: &T) -> Result<usize, Error> {
+                self.put_bytes(val.as_bytes())
+            }
+
+            /// Writes the bytes of the object to the payload starting at `index` and returns the index
+            /// following them, like `txObj()` of the Arduino library, see [FrameBuilder.put_bytes_at()][put_bytes_at].
+            ///
+            /// [put_bytes_at]: FrameBuilder::put_bytes_at()
+            pub fn put_at<T: AsBytes + ?Sized>(&mut self, index: usize, val: &T) -> Result<usize, Error> {
+                self.put_bytes_at(index, val.as_bytes())
+            }
+        }
+    }
+}
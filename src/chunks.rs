@@ -0,0 +1,72 @@
+//! Reading bytes in chunks and parsing them, shared by the transports over byte streams.
+
+use crate::{Clock, Error, SerMsg};
+
+// The length of the chunks the transports for no_std targets read and write
+#[cfg(any(feature = "embedded-hal", feature = "embedded-io-async"))]
+pub(crate) const CHUNK_LEN: usize = 32;
+
+// The last chunk of bytes read from a port and how far it was parsed. A chunk is only replaced
+// once it was parsed completely, so the bytes behind a completed message are parsed by the next
+// call instead of being lost.
+pub(crate) struct RxChunks<const L: usize> {
+    buf: [u8; L],
+    start: usize,
+    end: usize,
+}
+
+impl<const L: usize> RxChunks<L> {
+    pub(crate) const fn new() -> RxChunks<L> {
+        RxChunks {
+            buf: [0; L],
+            start: 0,
+            end: 0,
+        }
+    }
+
+    // Returns the buffer to read the next chunk into, followed by a call to filled()
+    pub(crate) fn buf_mut(&mut self) -> &mut [u8] {
+        &mut self.buf
+    }
+
+    // Marks the first `read_bytes` bytes of the buffer as the next chunk to parse
+    pub(crate) fn filled(&mut self, read_bytes: usize) {
+        self.start = 0;
+        self.end = read_bytes;
+    }
+
+    // Returns true if the chunk and the bytes kept for the resync of the parser were parsed
+    pub(crate) fn is_parsed(&self, ser_msg: &SerMsg) -> bool {
+        self.start == self.end && !ser_msg.has_resync_bytes()
+    }
+
+    // Parses the rest of the chunk until a message is completed or an error occurred.
+    // Returns true if a message was completed, false if the chunk was parsed completely.
+    pub(crate) fn parse<K: Clock>(
+        &mut self,
+        ser_msg: &mut SerMsg,
+        clock: Option<&K>,
+    ) -> Result<bool, Error> {
+        while !self.is_parsed(ser_msg) {
+            let (result, parsed_bytes) =
+                ser_msg.parse_bytes(&self.buf[self.start..self.end], clock);
+            self.start += parsed_bytes;
+            if result? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+// Fills the chunk with the next bytes of the message, returns the filled part.
+// An empty slice is returned once the message was written completely.
+#[cfg(any(feature = "embedded-hal", feature = "embedded-io-async"))]
+pub(crate) fn next_chunk<'c>(chunk: &'c mut [u8], msg: &mut impl Iterator<Item = u8>) -> &'c [u8] {
+    let len = chunk
+        .iter_mut()
+        .zip(msg)
+        .map(|(dst, val)| *dst = val)
+        .count();
+    &chunk[..len]
+}
@@ -0,0 +1,106 @@
+//! Time sources used to detect stale packets while parsing.
+//!
+//! The parser only needs a millisecond counter, similar to `millis()` on an Arduino.
+//! Implement [Clock] for your own timer or use one of the provided implementations.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// A monotonic millisecond counter used by [SerMsg.parse_read_bytes_timed()][parse_read_bytes_timed].
+///
+/// The counter is allowed to wrap around, elapsed times are calculated with wrapping arithmetic.
+///
+/// [parse_read_bytes_timed]: crate::SerMsg::parse_read_bytes_timed()
+pub trait Clock {
+    /// Returns the current time in milliseconds
+    fn now_ms(&self) -> u32;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now_ms(&self) -> u32 {
+        (**self).now_ms()
+    }
+}
+
+/// no_std clock that counts ticks of a periodic timer, e.g. a SysTick interrupt firing every millisecond.
+///
+/// The clock can be placed in a `static` and advanced from an interrupt handler
+/// while the parser reads it from the main loop.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{Clock, TickClock};
+///
+/// static CLOCK: TickClock = TickClock::new();
+///
+/// // Called from the timer interrupt
+/// CLOCK.tick();
+/// CLOCK.advance(9);
+/// assert_eq!(CLOCK.now_ms(), 10);
+/// ```
+#[derive(Debug, Default)]
+pub struct TickClock {
+    ticks: AtomicU32,
+}
+
+impl TickClock {
+    /// Returns a new TickClock starting at 0 ms
+    pub const fn new() -> TickClock {
+        TickClock {
+            ticks: AtomicU32::new(0),
+        }
+    }
+
+    /// Advances the clock by one millisecond
+    pub fn tick(&self) {
+        self.advance(1);
+    }
+
+    /// Advances the clock by the given amount of milliseconds.
+    /// Should only be called from a single context, e.g. one interrupt handler.
+    pub fn advance(&self, ms: u32) {
+        // load and store instead of fetch_add, so targets without atomic
+        // read-modify-write instructions (e.g. thumbv6m) are supported
+        let ticks = self.ticks.load(Ordering::Relaxed);
+        self.ticks.store(ticks.wrapping_add(ms), Ordering::Relaxed);
+    }
+}
+
+impl Clock for TickClock {
+    fn now_ms(&self) -> u32 {
+        self.ticks.load(Ordering::Relaxed)
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="std")] {
+        use std::time::Instant;
+
+        /// Clock based on [std::time::Instant], counting milliseconds since its creation
+        #[derive(Debug, Clone, Copy)]
+        pub struct StdClock {
+            origin: Instant,
+        }
+
+        impl StdClock {
+            /// Returns a new StdClock starting at 0 ms
+            pub fn new() -> StdClock {
+                StdClock {
+                    origin: Instant::now(),
+                }
+            }
+        }
+
+        impl Default for StdClock {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl Clock for StdClock {
+            fn now_ms(&self) -> u32 {
+                self.origin.elapsed().as_millis() as u32
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Reading and writing messages over asynchronous streams with a [tokio_util::codec::Framed].
+
+use std::io;
+
+use bytes::{Buf, Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{Crc, Crc8, Error, FramingConfig, Message, SerMsg};
+
+/// What a [SerMsgCodec][SerMsgCodec] does with a message that fails to decode, e.g. because of
+/// a CRC mismatch or a broken Consistent Overhead Byte Stuffing.
+///
+/// [SerMsgCodec]: SerMsgCodec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnFrameError {
+    /// The message is dropped and decoding continues with the next message
+    Skip,
+    /// An [io::Error] of the kind [InvalidData][InvalidData] wrapping the [Error][Error] is
+    /// returned. A `FramedRead` ends after returning an error.
+    ///
+    /// [InvalidData]: io::ErrorKind::InvalidData
+    /// [Error]: Error
+    Yield,
+}
+
+/// A [Decoder] of [Messages][Message] and an [Encoder] of `(id, payload)` tuples, to read and
+/// write messages with a [Framed][Framed] over e.g. a serial port of the `tokio-serial` crate.
+///
+/// Messages are decoded directly from the read buffer once they are complete. If a message
+/// fails to decode, only its start byte is dropped and the search for the next start byte
+/// continues behind it, like the resync of [SerMsg][SerMsg]. Bytes that are not part of a
+/// message are dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use bytes::Bytes;
+/// use futures::{SinkExt, StreamExt};
+/// use serialmessage::SerMsgCodec;
+/// use tokio_util::codec::Framed;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let (port, device) = tokio::io::duplex(64);
+/// let mut port = Framed::new(port, SerMsgCodec::new());
+/// let mut device = Framed::new(device, SerMsgCodec::new());
+///
+/// port.send((1, Bytes::from_static(&[1, 2, 3]))).await.unwrap();
+/// let msg = device.next().await.unwrap().unwrap();
+/// assert_eq!((msg.id, &msg.payload[..]), (1, &[1, 2, 3][..]));
+/// # });
+/// ```
+///
+/// [Message]: Message
+/// [Framed]: tokio_util::codec::Framed
+/// [SerMsg]: SerMsg
+#[derive(Debug, Clone)]
+pub struct SerMsgCodec<C = Crc8> {
+    config: FramingConfig,
+    crc: C,
+    on_error: OnFrameError,
+}
+
+impl Default for SerMsgCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SerMsgCodec {
+    /// Returns a new codec skipping messages that fail to decode
+    pub fn new() -> SerMsgCodec {
+        SerMsgCodec::with_config(FramingConfig::DEFAULT)
+    }
+
+    /// Returns a new codec using the [FramingConfig][FramingConfig],
+    /// skipping messages that fail to decode
+    ///
+    /// [FramingConfig]: FramingConfig
+    pub fn with_config(config: FramingConfig) -> SerMsgCodec {
+        SerMsgCodec::with_crc(config, Crc8::new())
+    }
+}
+
+impl<C: Crc> SerMsgCodec<C> {
+    /// Returns a new codec using the [FramingConfig][FramingConfig] and the [Crc][Crc] `crc`,
+    /// skipping messages that fail to decode
+    ///
+    /// [FramingConfig]: FramingConfig
+    /// [Crc]: Crc
+    pub fn with_crc(config: FramingConfig, crc: C) -> SerMsgCodec<C> {
+        SerMsgCodec {
+            config,
+            crc,
+            on_error: OnFrameError::Skip,
+        }
+    }
+
+    /// Returns the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: FramingConfig
+    pub fn config(&self) -> FramingConfig {
+        self.config
+    }
+
+    /// Returns what happens to messages that fail to decode
+    pub fn on_error(&self) -> OnFrameError {
+        self.on_error
+    }
+
+    /// Sets what happens to messages that fail to decode
+    pub fn set_on_error(&mut self, on_error: OnFrameError) {
+        self.on_error = on_error;
+    }
+
+    // Drops the start byte of the failed message
+    fn frame_error(&self, src: &mut BytesMut, e: Error) -> Result<(), io::Error> {
+        src.advance(1);
+        match self.on_error {
+            OnFrameError::Skip => Ok(()),
+            OnFrameError::Yield => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+}
+
+impl<C: Crc> Decoder for SerMsgCodec<C> {
+    type Item = Message;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Message>, io::Error> {
+        loop {
+            match src.iter().position(|&val| val == self.config.start_byte()) {
+                Some(pos) => src.advance(pos),
+                None => {
+                    src.clear();
+                    return Ok(None);
+                }
+            }
+            if src.len() < 4 {
+                return Ok(None);
+            }
+            let payload_len = src[3];
+            if payload_len > self.config.max_payload() {
+                self.frame_error(src, Error::HighPayload { len: payload_len })?;
+                continue;
+            }
+            let frame_len = SerMsg::encoded_len_crc::<C>(payload_len as usize);
+            if src.len() < frame_len {
+                src.reserve(frame_len - src.len());
+                return Ok(None);
+            }
+            let result = self
+                .config
+                .decode_frame_crc(&mut self.crc, &src[..frame_len])
+                .map(|(id, payload)| Message {
+                    id,
+                    payload: payload.iter().collect(),
+                });
+            match result {
+                Ok(msg) => {
+                    src.advance(frame_len);
+                    return Ok(Some(msg));
+                }
+                Err(e) => self.frame_error(src, e)?,
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Message>, io::Error> {
+        loop {
+            if let Some(msg) = self.decode(src)? {
+                return Ok(Some(msg));
+            }
+            if src.is_empty() {
+                return Ok(None);
+            }
+            // The message is cut off by the end of the stream
+            let len = src.len();
+            self.frame_error(src, Error::FrameLength { len })?;
+        }
+    }
+}
+
+/// Encodes the payload into the message format with the packet id, returns an [io::Error] of
+/// the kind [InvalidInput][InvalidInput] wrapping the [Error][Error] if the message can't be
+/// created.
+///
+/// [InvalidInput]: io::ErrorKind::InvalidInput
+/// [Error]: Error
+impl<C: Crc> Encoder<(u8, Bytes)> for SerMsgCodec<C> {
+    type Error = io::Error;
+
+    fn encode(&mut self, (id, payload): (u8, Bytes), dst: &mut BytesMut) -> Result<(), io::Error> {
+        let parts = [&payload[..]];
+        let msg = self
+            .config
+            .encode_slices_iter_crc(&mut self.crc, &parts, id)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        dst.extend(msg);
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+//! Framing parameters for devices using different start and stop bytes or a smaller payload limit.
+
+use crate::crc::{self, Crc};
+use crate::{Crc8, Error, SerMsg};
+
+/// The start byte, stop byte and maximum payload length of the message format.
+///
+/// The [default][DEFAULT] matches the Arduino SerialTransfer library and is used by all
+/// associated functions of [SerMsg][SerMsg]. Forks of SerialTransfer with different delimiters or
+/// a smaller payload limit are supported by creating messages with the methods of the
+/// configuration and by parsing them with [SerMsg::with_config()][with_config].
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{FramingConfig, SerMsg};
+///
+/// const CONFIG: FramingConfig = FramingConfig::new()
+///     .with_start_byte(0x02)
+///     .with_stop_byte(0x03)
+///     .with_max_payload(128);
+///
+/// let send_msg = CONFIG.encode_vec(&[1, 2, 3], 1).unwrap();
+/// assert!(CONFIG.encode_vec(&[0; 129], 1).is_err());
+///
+/// let mut ser_msg = SerMsg::with_config(CONFIG);
+/// let mut bytes = &send_msg[..];
+/// let frame = ser_msg.parse(&mut bytes).unwrap().unwrap();
+/// assert_eq!(frame.payload, &[1, 2, 3]);
+/// ```
+///
+/// [DEFAULT]: FramingConfig::DEFAULT
+/// [SerMsg]: SerMsg
+/// [with_config]: SerMsg::with_config()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FramingConfig {
+    start_byte: u8,
+    stop_byte: u8,
+    max_payload: u8,
+}
+
+impl Default for FramingConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl FramingConfig {
+    /// The framing of the Arduino SerialTransfer library, with the start byte 0x7E,
+    /// the stop byte 0x81 and a maximum payload length of 254 bytes
+    pub const DEFAULT: FramingConfig = FramingConfig {
+        start_byte: 0x7E,
+        stop_byte: 0x81,
+        max_payload: SerMsg::MAX_PACKET_SIZE,
+    };
+
+    /// Returns the [default][DEFAULT] configuration
+    ///
+    /// [DEFAULT]: FramingConfig::DEFAULT
+    pub const fn new() -> FramingConfig {
+        FramingConfig::DEFAULT
+    }
+
+    /// Sets the byte every message starts with, which is also replaced in the payload
+    /// by the Consistent Overhead Byte Stuffing
+    pub const fn with_start_byte(mut self, start_byte: u8) -> FramingConfig {
+        self.start_byte = start_byte;
+        self
+    }
+
+    /// Sets the byte every message ends with
+    pub const fn with_stop_byte(mut self, stop_byte: u8) -> FramingConfig {
+        self.stop_byte = stop_byte;
+        self
+    }
+
+    /// Sets the maximum payload length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length exceeds 254 bytes, the maximum the message format supports.
+    pub const fn with_max_payload(mut self, max_payload: u8) -> FramingConfig {
+        assert!(
+            max_payload <= SerMsg::MAX_PACKET_SIZE,
+            "the maximum payload length can't exceed 254 bytes"
+        );
+        self.max_payload = max_payload;
+        self
+    }
+
+    /// Returns the byte every message starts with
+    pub const fn start_byte(&self) -> u8 {
+        self.start_byte
+    }
+
+    /// Returns the byte every message ends with
+    pub const fn stop_byte(&self) -> u8 {
+        self.stop_byte
+    }
+
+    /// Returns the maximum payload length
+    pub const fn max_payload(&self) -> u8 {
+        self.max_payload
+    }
+
+    /// Same as [SerMsg::encode_into()][encode_into] with this configuration.
+    /// Returns [PayloadTooLarge][PayloadTooLarge] if the input slice exceeds the maximum payload length.
+    ///
+    /// [encode_into]: SerMsg::encode_into()
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    pub fn encode_into(&self, data: &[u8], id: u8, out: &mut [u8]) -> Result<usize, Error> {
+        self.encode_slices_into(&[data], id, out)
+    }
+
+    /// Same as [SerMsg::encode_slices_into()][encode_slices_into] with this configuration.
+    /// Returns [PayloadTooLarge][PayloadTooLarge] if the slices exceed the maximum payload length.
+    ///
+    /// [encode_slices_into]: SerMsg::encode_slices_into()
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    pub fn encode_slices_into(
+        &self,
+        parts: &[&[u8]],
+        id: u8,
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        self.encode_slices_into_crc(&mut Crc8::new(), parts, id, out)
+    }
+
+    /// Same as [FramingConfig.encode_slices_into()][encode_slices_into], but the CRC is
+    /// calculated with the [Crc][Crc] `crc`. The length of the message is
+    /// [SerMsg::encoded_len_crc()][encoded_len_crc] of the payload length.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::{Crc16, FramingConfig, SerMsg};
+    ///
+    /// let mut tx_buffer = [0; 64];
+    /// let msg_len = FramingConfig::DEFAULT
+    ///     .encode_slices_into_crc(&mut Crc16::new(), &[&[1, 2, 3]], 1, &mut tx_buffer)
+    ///     .unwrap();
+    /// assert_eq!(msg_len, SerMsg::encoded_len_crc::<Crc16>(3));
+    /// ```
+    ///
+    /// [encode_slices_into]: FramingConfig::encode_slices_into()
+    /// [Crc]: Crc
+    /// [encoded_len_crc]: SerMsg::encoded_len_crc()
+    pub fn encode_slices_into_crc<C: Crc>(
+        &self,
+        crc: &mut C,
+        parts: &[&[u8]],
+        id: u8,
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        let data_len = self.slices_len(parts)?;
+        let msg_len = SerMsg::encoded_len_crc::<C>(data_len);
+        if out.len() < msg_len {
+            return Err(Error::BufferTooSmall {
+                len: out.len(),
+                needed: msg_len,
+            });
+        }
+        out[0] = self.start_byte;
+        out[1] = id;
+        out[3] = data_len as u8;
+        let mut ind = 4;
+        for part in parts {
+            out[ind..ind + part.len()].copy_from_slice(part);
+            ind += part.len();
+        }
+        out[2] = SerMsg::pack_cobs(self.start_byte, &mut out[4..4 + data_len]);
+        let calc_crc = crc.checksum(&out[4..4 + data_len]);
+        crc::write_crc::<C>(calc_crc, &mut out[4 + data_len..]);
+        out[msg_len - 1] = self.stop_byte;
+        Ok(msg_len)
+    }
+
+    pub(crate) fn slices_len(&self, parts: &[&[u8]]) -> Result<usize, Error> {
+        let data_len = parts.iter().map(|part| part.len()).sum();
+        if data_len > self.max_payload as usize {
+            return Err(Error::PayloadTooLarge { len: data_len });
+        }
+        Ok(data_len)
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="alloc")] {
+        use alloc::vec::Vec;
+
+        impl FramingConfig {
+            /// Same as [SerMsg::encode_vec()][encode_vec] with this configuration.
+            /// Returns [PayloadTooLarge][PayloadTooLarge] if the input slice exceeds the maximum payload length.
+            ///
+            /// [encode_vec]: SerMsg::encode_vec()
+            /// [PayloadTooLarge]: Error::PayloadTooLarge
+            pub fn encode_vec(&self, data: &[u8], id: u8) -> Result<Vec<u8>, Error> {
+                self.encode_slices_vec(&[data], id)
+            }
+
+            /// Same as [SerMsg::encode_extend()][encode_extend] with this configuration.
+            /// Returns [PayloadTooLarge][PayloadTooLarge] if the input slice exceeds the maximum payload length.
+            ///
+            /// [encode_extend]: SerMsg::encode_extend()
+            /// [PayloadTooLarge]: Error::PayloadTooLarge
+            pub fn encode_extend(&self, data: &[u8], id: u8, data_vec: &mut Vec<u8>) -> Result<usize, Error> {
+                self.encode_slices_extend(&[data], id, data_vec)
+            }
+
+            /// Same as [SerMsg::encode_slices_vec()][encode_slices_vec] with this configuration.
+            /// Returns [PayloadTooLarge][PayloadTooLarge] if the slices exceed the maximum payload length.
+            ///
+            /// [encode_slices_vec]: SerMsg::encode_slices_vec()
+            /// [PayloadTooLarge]: Error::PayloadTooLarge
+            pub fn encode_slices_vec(&self, parts: &[&[u8]], id: u8) -> Result<Vec<u8>, Error> {
+                let mut data_vec: Vec<u8> = Vec::new();
+                self.encode_slices_extend(parts, id, &mut data_vec)?;
+                Ok(data_vec)
+            }
+
+            /// Same as [SerMsg::encode_slices_extend()][encode_slices_extend] with this configuration.
+            /// Returns [PayloadTooLarge][PayloadTooLarge] if the slices exceed the maximum payload length.
+            ///
+            /// [encode_slices_extend]: SerMsg::encode_slices_extend()
+            /// [PayloadTooLarge]: Error::PayloadTooLarge
+            pub fn encode_slices_extend(&self, parts: &[&[u8]], id: u8, data_vec: &mut Vec<u8>) -> Result<usize, Error> {
+                self.encode_slices_extend_crc(&mut Crc8::new(), parts, id, data_vec)
+            }
+
+            /// Same as [FramingConfig.encode_slices_vec()][encode_slices_vec], but the CRC is
+            /// calculated with the [Crc][Crc] `crc`.
+            ///
+            /// [encode_slices_vec]: FramingConfig::encode_slices_vec()
+            /// [Crc]: Crc
+            pub fn encode_slices_vec_crc<C: Crc>(&self, crc: &mut C, parts: &[&[u8]], id: u8) -> Result<Vec<u8>, Error> {
+                let mut data_vec: Vec<u8> = Vec::new();
+                self.encode_slices_extend_crc(crc, parts, id, &mut data_vec)?;
+                Ok(data_vec)
+            }
+
+            /// Same as [FramingConfig.encode_slices_extend()][encode_slices_extend], but the CRC is
+            /// calculated with the [Crc][Crc] `crc`.
+            ///
+            /// [encode_slices_extend]: FramingConfig::encode_slices_extend()
+            /// [Crc]: Crc
+            pub fn encode_slices_extend_crc<C: Crc>(&self, crc: &mut C, parts: &[&[u8]], id: u8, data_vec: &mut Vec<u8>) -> Result<usize, Error> {
+                let data_len = self.slices_len(parts)?;
+                let old_len = data_vec.len();
+                data_vec.resize(old_len + SerMsg::encoded_len_crc::<C>(data_len), 0);
+                self.encode_slices_into_crc(crc, parts, id, &mut data_vec[old_len..])
+            }
+        }
+    }
+}
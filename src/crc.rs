@@ -0,0 +1,368 @@
+//! Cyclic redundancy checks protecting the payload of a message.
+//!
+//! The message format uses [Crc8] with the polynomial 0x9B by default. Other CRC-8 polynomials,
+//! the wider [Crc16] and [Crc32] trailers or a CRC peripheral of a Microcontroller can be used
+//! by implementing [Crc] and creating the parser with [SerMsg::with_crc()][with_crc].
+//! Both sides of the connection have to use the same CRC, the wider trailers are not
+//! supported by the Arduino SerialTransfer library.
+//!
+//! [with_crc]: crate::SerMsg::with_crc()
+
+use core::marker::PhantomData;
+
+/// A CRC calculated over the payload of a message and appended to it.
+///
+/// The CRC is sent with its most significant byte first. Implement this trait
+/// to use a hardware CRC peripheral instead of the lookup tables of this crate.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{Crc, SerMsg};
+///
+/// // Stands in for the registers of a CRC peripheral
+/// #[derive(Default)]
+/// struct HardwareCrc {
+///     data_register: u8,
+/// }
+///
+/// impl Crc for HardwareCrc {
+///     const LEN: usize = 1;
+///
+///     fn reset(&mut self) {
+///         self.data_register = 0;
+///     }
+///
+///     fn update(&mut self, data: &[u8]) {
+///         for val in data {
+///             self.data_register ^= val;
+///             for _ in 0..8 {
+///                 self.data_register = match self.data_register & 0x80 {
+///                     0 => self.data_register << 1,
+///                     _ => (self.data_register << 1) ^ 0x9B,
+///                 };
+///             }
+///         }
+///     }
+///
+///     fn finish(&self) -> u32 {
+///         self.data_register as u32
+///     }
+/// }
+///
+/// let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+/// let mut ser_msg = SerMsg::with_crc(Default::default(), HardwareCrc::default());
+/// let mut bytes = &send_msg[..];
+/// assert!(ser_msg.parse(&mut bytes).unwrap().is_some());
+/// ```
+pub trait Crc {
+    /// The amount of bytes the CRC takes up in a message, from 1 to 4.
+    /// Encoding or parsing messages with a Crc of another length fails to compile.
+    ///
+    /// ```rust,compile_fail
+    /// use serialmessage::{Crc, SerMsg};
+    ///
+    /// struct NoCrc;
+    ///
+    /// impl Crc for NoCrc {
+    ///     const LEN: usize = 0;
+    ///
+    ///     fn reset(&mut self) {}
+    ///
+    ///     fn update(&mut self, _data: &[u8]) {}
+    ///
+    ///     fn finish(&self) -> u32 {
+    ///         0
+    ///     }
+    /// }
+    ///
+    /// let msg_len = SerMsg::encoded_len_crc::<NoCrc>(3);
+    /// ```
+    const LEN: usize;
+
+    /// Resets the CRC to its initial value
+    fn reset(&mut self);
+
+    /// Updates the CRC with the bytes
+    fn update(&mut self, data: &[u8]);
+
+    /// Returns the CRC of all bytes since the last reset
+    fn finish(&self) -> u32;
+
+    /// Returns the CRC of the bytes
+    fn checksum(&mut self, data: &[u8]) -> u32 {
+        self.reset();
+        self.update(data);
+        self.finish()
+    }
+}
+
+impl<C: Crc + ?Sized> Crc for &mut C {
+    const LEN: usize = C::LEN;
+
+    fn reset(&mut self) {
+        (**self).reset();
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        (**self).update(data);
+    }
+
+    fn finish(&self) -> u32 {
+        (**self).finish()
+    }
+}
+
+/// Returns the lookup table of a CRC-8 with the polynomial, processing the most significant bit first.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{crc8_table, Crc, Crc8};
+///
+/// static CRC8_SMBUS: [u8; 256] = crc8_table(0x07);
+///
+/// let mut crc = Crc8::with_table(&CRC8_SMBUS);
+/// assert_eq!(crc.checksum(b"123456789"), 0xF4);
+/// ```
+pub const fn crc8_table(poly: u8) -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 0x80 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ poly,
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Returns the lookup table of a CRC-16 with the polynomial, processing the most significant bit first.
+pub const fn crc16_table(poly: u16) -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 0x8000 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ poly,
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Returns the lookup table of a CRC-32 with the polynomial, processing the most significant bit first.
+pub const fn crc32_table(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 0x8000_0000 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ poly,
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// CRC-8 calculated with a lookup table, the default CRC of the message format.
+///
+/// [Crc8::new()][new] uses the polynomial 0x9B of the Arduino SerialTransfer library,
+/// tables of other polynomials are generated with [crc8_table()][crc8_table].
+///
+/// [new]: Crc8::new()
+/// [crc8_table]: crc8_table()
+#[derive(Debug, Clone)]
+pub struct Crc8 {
+    table: &'static [u8; 256],
+    crc: u8,
+}
+
+impl Default for Crc8 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc8 {
+    /// The lookup table of the polynomial 0x9B
+    pub const TABLE: [u8; 256] = crc8_table(0x9B);
+
+    /// Returns a CRC-8 with the polynomial 0x9B
+    pub const fn new() -> Crc8 {
+        Crc8::with_table(&Crc8::TABLE)
+    }
+
+    /// Returns a CRC-8 using the lookup table
+    pub const fn with_table(table: &'static [u8; 256]) -> Crc8 {
+        Crc8 { table, crc: 0 }
+    }
+}
+
+impl Crc for Crc8 {
+    const LEN: usize = 1;
+
+    fn reset(&mut self) {
+        self.crc = 0;
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for val in data.iter() {
+            self.crc = self.table[(self.crc ^ val) as usize];
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.crc as u32
+    }
+}
+
+/// CRC-16 calculated with a lookup table, for a stronger check than the default [Crc8].
+///
+/// [Crc16::new()][new] uses the polynomial 0x1021 with an initial value of 0 (CRC-16/XMODEM),
+/// tables of other polynomials are generated with [crc16_table()][crc16_table].
+///
+/// [new]: Crc16::new()
+/// [crc16_table]: crc16_table()
+#[derive(Debug, Clone)]
+pub struct Crc16 {
+    table: &'static [u16; 256],
+    crc: u16,
+}
+
+impl Default for Crc16 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc16 {
+    /// The lookup table of the polynomial 0x1021
+    pub const TABLE: [u16; 256] = crc16_table(0x1021);
+
+    /// Returns a CRC-16 with the polynomial 0x1021
+    pub const fn new() -> Crc16 {
+        Crc16::with_table(&Crc16::TABLE)
+    }
+
+    /// Returns a CRC-16 using the lookup table
+    pub const fn with_table(table: &'static [u16; 256]) -> Crc16 {
+        Crc16 { table, crc: 0 }
+    }
+}
+
+impl Crc for Crc16 {
+    const LEN: usize = 2;
+
+    fn reset(&mut self) {
+        self.crc = 0;
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for val in data.iter() {
+            self.crc = (self.crc << 8) ^ self.table[((self.crc >> 8) as u8 ^ val) as usize];
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.crc as u32
+    }
+}
+
+/// CRC-32 calculated with a lookup table, for a stronger check than the default [Crc8].
+///
+/// [Crc32::new()][new] uses the polynomial 0x04C11DB7 with an initial value of 0 and no
+/// final XOR, tables of other polynomials are generated with [crc32_table()][crc32_table].
+///
+/// [new]: Crc32::new()
+/// [crc32_table]: crc32_table()
+#[derive(Debug, Clone)]
+pub struct Crc32 {
+    table: &'static [u32; 256],
+    crc: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    /// The lookup table of the polynomial 0x04C11DB7
+    pub const TABLE: [u32; 256] = crc32_table(0x04C1_1DB7);
+
+    /// Returns a CRC-32 with the polynomial 0x04C11DB7
+    pub const fn new() -> Crc32 {
+        Crc32::with_table(&Crc32::TABLE)
+    }
+
+    /// Returns a CRC-32 using the lookup table
+    pub const fn with_table(table: &'static [u32; 256]) -> Crc32 {
+        Crc32 { table, crc: 0 }
+    }
+}
+
+impl Crc for Crc32 {
+    const LEN: usize = 4;
+
+    fn reset(&mut self) {
+        self.crc = 0;
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for val in data.iter() {
+            self.crc = (self.crc << 8) ^ self.table[((self.crc >> 24) as u8 ^ val) as usize];
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.crc
+    }
+}
+
+// Evaluating the length fails the build if the Crc doesn't take up 1 to 4 bytes
+struct CrcLen<C>(PhantomData<C>);
+
+impl<C: Crc> CrcLen<C> {
+    const LEN: usize = {
+        assert!(C::LEN >= 1 && C::LEN <= 4, "Crc::LEN has to be 1 to 4");
+        C::LEN
+    };
+}
+
+// Returns the length of the Crc, used instead of Crc::LEN to check it
+pub(crate) const fn len<C: Crc>() -> usize {
+    CrcLen::<C>::LEN
+}
+
+// Writes the CRC into the slice with the most significant byte first
+pub(crate) fn write_crc<C: Crc>(crc: u32, out: &mut [u8]) {
+    out[..len::<C>()].copy_from_slice(&crc.to_be_bytes()[4 - len::<C>()..]);
+}
+
+// Reads the CRC from the slice with the most significant byte first
+pub(crate) fn read_crc<C: Crc>(data: &[u8]) -> u32 {
+    data[..len::<C>()]
+        .iter()
+        .fold(0, |crc, val| (crc << 8) | *val as u32)
+}
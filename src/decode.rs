@@ -0,0 +1,263 @@
+//! Decoding complete messages without the state machine of [SerMsg][SerMsg].
+//!
+//! [SerMsg]: crate::SerMsg
+
+use crate::crc::{self, Crc};
+use crate::{Crc8, Error, FramingConfig, SerMsg};
+
+/// The payload of a message decoded by [decode_frame()][decode_frame].
+///
+/// The payload borrows the still stuffed bytes of the message and replaces
+/// the stuffed bytes while reading it, so no bytes are copied during decoding.
+///
+/// [decode_frame]: decode_frame()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Payload<'a> {
+    stuffed_data: &'a [u8],
+    cobs_byte: u8,
+    start_byte: u8,
+}
+
+impl<'a> Payload<'a> {
+    /// Returns the amount of payload bytes
+    pub fn len(&self) -> usize {
+        self.stuffed_data.len()
+    }
+
+    /// Returns true if the payload contains no bytes
+    pub fn is_empty(&self) -> bool {
+        self.stuffed_data.is_empty()
+    }
+
+    /// Returns the payload bytes before the Consistent Overhead Byte Stuffing was removed
+    pub fn stuffed_data(&self) -> &'a [u8] {
+        self.stuffed_data
+    }
+
+    /// Returns an iterator over the unpacked payload bytes
+    pub fn iter(&self) -> PayloadIter<'a> {
+        PayloadIter {
+            stuffed_data: self.stuffed_data,
+            ind: 0,
+            next_cobs_ind: self.cobs_byte as usize,
+            start_byte: self.start_byte,
+        }
+    }
+
+    /// Copies the unpacked payload into the beginning of the slice and returns the amount of copied bytes.
+    /// Returns None if the slice is shorter than the payload.
+    pub fn copy_to_slice(&self, data_slice: &mut [u8]) -> Option<usize> {
+        if data_slice.len() < self.len() {
+            return None;
+        }
+        for (dest, val) in data_slice.iter_mut().zip(self.iter()) {
+            *dest = val;
+        }
+        Some(self.len())
+    }
+}
+
+impl<'a> IntoIterator for Payload<'a> {
+    type Item = u8;
+    type IntoIter = PayloadIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the unpacked bytes of a [Payload][Payload]
+///
+/// [Payload]: Payload
+#[derive(Debug, Clone)]
+pub struct PayloadIter<'a> {
+    stuffed_data: &'a [u8],
+    ind: usize,
+    next_cobs_ind: usize,
+    start_byte: u8,
+}
+
+impl Iterator for PayloadIter<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let val = *self.stuffed_data.get(self.ind)?;
+        let ind = self.ind;
+        self.ind += 1;
+        if ind == self.next_cobs_ind {
+            if val > 0 {
+                self.next_cobs_ind += val as usize;
+            }
+            Some(self.start_byte)
+        } else {
+            Some(val)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.stuffed_data.len() - self.ind;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for PayloadIter<'_> {}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="alloc")] {
+        use alloc::vec::Vec;
+
+        impl Payload<'_> {
+            /// Returns a Vector containing the unpacked payload
+            pub fn to_vec(&self) -> Vec<u8> {
+                self.iter().collect()
+            }
+        }
+    }
+}
+
+// Checks everything but the Consistent Overhead Byte Stuffing and
+// returns the id, the overhead byte and the payload length
+fn check_frame<C: Crc>(
+    config: &FramingConfig,
+    crc: &mut C,
+    frame: &[u8],
+) -> Result<(u8, u8, usize), Error> {
+    match frame.first() {
+        Some(&val) if val == config.start_byte() => (),
+        Some(&found) => return Err(Error::StartByte { found }),
+        None => return Err(Error::FrameLength { len: 0 }),
+    }
+    if frame.len() < SerMsg::encoded_len_crc::<C>(0) {
+        return Err(Error::FrameLength { len: frame.len() });
+    }
+    let payload_len = frame[3];
+    if payload_len > config.max_payload() {
+        return Err(Error::HighPayload { len: payload_len });
+    }
+    let payload_len = payload_len as usize;
+    if frame.len() != SerMsg::encoded_len_crc::<C>(payload_len) {
+        return Err(Error::FrameLength { len: frame.len() });
+    }
+    let calc_crc = crc.checksum(&frame[4..4 + payload_len]);
+    let rcvd_crc = crc::read_crc::<C>(&frame[4 + payload_len..]);
+    if rcvd_crc != calc_crc {
+        return Err(Error::Crc {
+            expected: calc_crc,
+            received: rcvd_crc,
+        });
+    }
+    let stop_byte = frame[frame.len() - 1];
+    if stop_byte != config.stop_byte() {
+        return Err(Error::StopByte { found: stop_byte });
+    }
+    Ok((frame[1], frame[2], payload_len))
+}
+
+/// Decodes a slice containing exactly one complete message and returns its id and payload.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{decode_frame, SerMsg};
+///
+/// let send_msg = SerMsg::create_msg_vec(&[1, 126, 3], 2).unwrap();
+/// let (id, payload) = decode_frame(&send_msg).unwrap();
+/// assert_eq!(id, 2);
+/// assert_eq!(payload.to_vec(), vec![1, 126, 3]);
+/// ```
+pub fn decode_frame(frame: &[u8]) -> Result<(u8, Payload<'_>), Error> {
+    FramingConfig::DEFAULT.decode_frame(frame)
+}
+
+/// Decodes a slice containing exactly one complete message by removing the Consistent Overhead
+/// Byte Stuffing inside the slice. Returns the id and the payload as a part of the slice.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{decode_frame_in_place, SerMsg};
+///
+/// let mut rcvd_bytes = SerMsg::create_msg_vec(&[1, 126, 3], 2).unwrap();
+/// let (id, payload) = decode_frame_in_place(&mut rcvd_bytes).unwrap();
+/// assert_eq!(id, 2);
+/// assert_eq!(payload, &[1, 126, 3]);
+/// ```
+pub fn decode_frame_in_place(frame: &mut [u8]) -> Result<(u8, &mut [u8]), Error> {
+    FramingConfig::DEFAULT.decode_frame_in_place(frame)
+}
+
+impl FramingConfig {
+    /// Same as [decode_frame()][decode_frame] with this configuration.
+    ///
+    /// [decode_frame]: decode_frame()
+    pub fn decode_frame<'a>(&self, frame: &'a [u8]) -> Result<(u8, Payload<'a>), Error> {
+        self.decode_frame_crc(&mut Crc8::new(), frame)
+    }
+
+    /// Same as [FramingConfig.decode_frame()][decode_frame], but the CRC is checked
+    /// with the [Crc][Crc] `crc`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::{Crc32, FramingConfig};
+    ///
+    /// let config = FramingConfig::DEFAULT;
+    /// let send_msg = config
+    ///     .encode_slices_vec_crc(&mut Crc32::new(), &[&[1, 126, 3]], 2)
+    ///     .unwrap();
+    /// let (id, payload) = config.decode_frame_crc(&mut Crc32::new(), &send_msg).unwrap();
+    /// assert_eq!(id, 2);
+    /// assert_eq!(payload.to_vec(), vec![1, 126, 3]);
+    /// ```
+    ///
+    /// [decode_frame]: FramingConfig::decode_frame()
+    /// [Crc]: Crc
+    pub fn decode_frame_crc<'a, C: Crc>(
+        &self,
+        crc: &mut C,
+        frame: &'a [u8],
+    ) -> Result<(u8, Payload<'a>), Error> {
+        let (id, cobs_byte, payload_len) = check_frame(self, crc, frame)?;
+        let stuffed_data = &frame[4..4 + payload_len];
+        if !SerMsg::check_cobs(cobs_byte, stuffed_data) {
+            return Err(Error::Cobs);
+        }
+        Ok((
+            id,
+            Payload {
+                stuffed_data,
+                cobs_byte,
+                start_byte: self.start_byte(),
+            },
+        ))
+    }
+
+    /// Same as [decode_frame_in_place()][decode_frame_in_place] with this configuration.
+    ///
+    /// [decode_frame_in_place]: decode_frame_in_place()
+    pub fn decode_frame_in_place<'a>(
+        &self,
+        frame: &'a mut [u8],
+    ) -> Result<(u8, &'a mut [u8]), Error> {
+        self.decode_frame_in_place_crc(&mut Crc8::new(), frame)
+    }
+
+    /// Same as [FramingConfig.decode_frame_in_place()][decode_frame_in_place], but the CRC is
+    /// checked with the [Crc][Crc] `crc`.
+    ///
+    /// [decode_frame_in_place]: FramingConfig::decode_frame_in_place()
+    /// [Crc]: Crc
+    pub fn decode_frame_in_place_crc<'a, C: Crc>(
+        &self,
+        crc: &mut C,
+        frame: &'a mut [u8],
+    ) -> Result<(u8, &'a mut [u8]), Error> {
+        let (id, cobs_byte, payload_len) = check_frame(self, crc, frame)?;
+        let payload = &mut frame[4..4 + payload_len];
+        if !SerMsg::unpack_cobs(self.start_byte(), cobs_byte, payload) {
+            return Err(Error::Cobs);
+        }
+        Ok((id, payload))
+    }
+}
@@ -0,0 +1,341 @@
+//! Sending and receiving messages over the serial traits of embedded HALs.
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial;
+use embedded_io::{Read, ReadReady, Write};
+
+use crate::chunks::{next_chunk, RxChunks, CHUNK_LEN};
+use crate::{Error, FrameRef, FramingConfig, Message, SerMsg, TickClock, TransferError};
+
+/// Sends and receives messages over a port implementing the traits of `embedded-io`, the
+/// counterpart of [Transfer][Transfer] for `no_std` targets.
+///
+/// Received bytes are read in chunks of up to 32 bytes and messages are written in chunks of up
+/// to 32 bytes, so there are no buffers holding a whole message besides the parser.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{EmbeddedTransfer, SerMsg};
+///
+/// // Stands in for the receiving half of a UART
+/// let rcvd_bytes = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+/// let mut rx = EmbeddedTransfer::new(&rcvd_bytes[..]);
+/// let msg = rx.receive().unwrap();
+/// assert_eq!((msg.id, &msg.payload[..]), (1, &[1, 2, 3][..]));
+///
+/// // Stands in for the sending half of a UART
+/// let mut tx_buffer = [0; 64];
+/// let mut tx = EmbeddedTransfer::new(&mut tx_buffer[..]);
+/// tx.send(&[1, 2, 3], 1).unwrap();
+/// assert_eq!(&tx_buffer[..rcvd_bytes.len()], &rcvd_bytes[..]);
+/// ```
+///
+/// [Transfer]: crate::Transfer
+pub struct EmbeddedTransfer<U> {
+    port: U,
+    ser_msg: SerMsg,
+    rx_chunks: RxChunks<CHUNK_LEN>,
+}
+
+impl<U> EmbeddedTransfer<U> {
+    /// Returns a new EmbeddedTransfer sending and receiving over the port
+    pub fn new(port: U) -> EmbeddedTransfer<U> {
+        EmbeddedTransfer::with_config(port, FramingConfig::DEFAULT)
+    }
+
+    /// Returns a new EmbeddedTransfer sending and receiving over the port
+    /// using the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: FramingConfig
+    pub fn with_config(port: U, config: FramingConfig) -> EmbeddedTransfer<U> {
+        EmbeddedTransfer {
+            port,
+            ser_msg: SerMsg::with_config(config),
+            rx_chunks: RxChunks::new(),
+        }
+    }
+
+    /// Returns the port
+    pub fn port(&self) -> &U {
+        &self.port
+    }
+
+    /// Returns the port, e.g. to change its settings
+    pub fn port_mut(&mut self) -> &mut U {
+        &mut self.port
+    }
+
+    /// Returns the port, dropping the bytes received but not parsed yet
+    pub fn into_inner(self) -> U {
+        self.port
+    }
+
+    /// Returns the parser, e.g. to enable the resync
+    pub fn parser_mut(&mut self) -> &mut SerMsg {
+        &mut self.ser_msg
+    }
+
+    /// Returns the payload of the message completed by [EmbeddedTransfer.poll()][poll]
+    ///
+    /// [poll]: EmbeddedTransfer::poll()
+    pub fn payload(&self) -> &[u8] {
+        self.ser_msg.return_read_data()
+    }
+
+    /// Returns the id of the message completed by [EmbeddedTransfer.poll()][poll]
+    ///
+    /// [poll]: EmbeddedTransfer::poll()
+    pub fn packet_id(&self) -> u8 {
+        self.ser_msg.return_msg_id()
+    }
+
+    // Parses the bytes left from the last read, returns true if a message was completed
+    fn parse_rx_chunk(&mut self) -> Result<bool, Error> {
+        self.rx_chunks.parse::<TickClock>(&mut self.ser_msg, None)
+    }
+}
+
+impl<U: Read> EmbeddedTransfer<U> {
+    /// Blocks until the next message is completed and returns it.
+    /// Returns [Eof][Eof] if the port reached its end.
+    ///
+    /// [Eof]: TransferError::Eof
+    pub fn receive(&mut self) -> Result<Message, TransferError<U::Error>> {
+        while !self.parse_rx_chunk()? {
+            self.read()?;
+        }
+        Ok(Message::from_parsed(FrameRef {
+            id: self.packet_id(),
+            payload: self.payload(),
+        }))
+    }
+
+    fn read(&mut self) -> Result<(), TransferError<U::Error>> {
+        let read_bytes = self
+            .port
+            .read(self.rx_chunks.buf_mut())
+            .map_err(TransferError::Port)?;
+        if read_bytes == 0 {
+            return Err(TransferError::Eof);
+        }
+        self.rx_chunks.filled(read_bytes);
+        Ok(())
+    }
+}
+
+impl<U: Read + ReadReady> EmbeddedTransfer<U> {
+    /// Parses the available bytes without blocking, like `available()` of the Arduino library.
+    /// Returns true if a message was completed, its payload and id are then returned by
+    /// [EmbeddedTransfer.payload()][payload] and [EmbeddedTransfer.packet_id()][packet_id]
+    /// until this function is called again. The bytes behind the message are parsed by the
+    /// next call.
+    ///
+    /// [payload]: EmbeddedTransfer::payload()
+    /// [packet_id]: EmbeddedTransfer::packet_id()
+    pub fn poll(&mut self) -> Result<bool, TransferError<U::Error>> {
+        loop {
+            if self.parse_rx_chunk()? {
+                return Ok(true);
+            }
+            if !self.port.read_ready().map_err(TransferError::Port)? {
+                return Ok(false);
+            }
+            self.read()?;
+        }
+    }
+}
+
+impl<U: Write> EmbeddedTransfer<U> {
+    /// Packs the slice into the message format and writes the message to the port,
+    /// like `sendData()` of the Arduino library
+    pub fn send(&mut self, data: &[u8], id: u8) -> Result<(), TransferError<U::Error>> {
+        let parts = [data];
+        let mut msg = self.ser_msg.config().encode_slices_iter(&parts, id)?;
+        let mut chunk = [0; CHUNK_LEN];
+        loop {
+            let bytes = next_chunk(&mut chunk, &mut msg);
+            if bytes.is_empty() {
+                break;
+            }
+            self.port.write_all(bytes).map_err(TransferError::Port)?;
+        }
+        self.port.flush().map_err(TransferError::Port)
+    }
+}
+
+/// Sends and receives messages over a port implementing the non-blocking serial traits of
+/// `embedded-hal-nb`, which transfer one byte at a time.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_hal_nb::nb;
+/// use embedded_hal_nb::serial::{ErrorType, Read};
+/// use serialmessage::{NbTransfer, SerMsg};
+///
+/// // Stands in for the receiving half of a UART
+/// struct Rx(std::vec::IntoIter<u8>);
+///
+/// impl ErrorType for Rx {
+///     type Error = core::convert::Infallible;
+/// }
+///
+/// impl Read for Rx {
+///     fn read(&mut self) -> nb::Result<u8, Self::Error> {
+///         self.0.next().ok_or(nb::Error::WouldBlock)
+///     }
+/// }
+///
+/// let rcvd_bytes = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+/// let mut rx = NbTransfer::new(Rx(rcvd_bytes.into_iter()));
+/// assert!(rx.poll().unwrap());
+/// assert_eq!((rx.packet_id(), rx.payload()), (1, &[1, 2, 3][..]));
+/// assert!(!rx.poll().unwrap());
+/// ```
+pub struct NbTransfer<U> {
+    port: U,
+    ser_msg: SerMsg,
+}
+
+impl<U> NbTransfer<U> {
+    /// Returns a new NbTransfer sending and receiving over the port
+    pub fn new(port: U) -> NbTransfer<U> {
+        NbTransfer::with_config(port, FramingConfig::DEFAULT)
+    }
+
+    /// Returns a new NbTransfer sending and receiving over the port
+    /// using the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: FramingConfig
+    pub fn with_config(port: U, config: FramingConfig) -> NbTransfer<U> {
+        NbTransfer {
+            port,
+            ser_msg: SerMsg::with_config(config),
+        }
+    }
+
+    /// Returns the port
+    pub fn port(&self) -> &U {
+        &self.port
+    }
+
+    /// Returns the port, e.g. to change its settings
+    pub fn port_mut(&mut self) -> &mut U {
+        &mut self.port
+    }
+
+    /// Returns the port
+    pub fn into_inner(self) -> U {
+        self.port
+    }
+
+    /// Returns the parser, e.g. to enable the resync
+    pub fn parser_mut(&mut self) -> &mut SerMsg {
+        &mut self.ser_msg
+    }
+
+    /// Returns the payload of the message completed by [NbTransfer.poll()][poll]
+    ///
+    /// [poll]: NbTransfer::poll()
+    pub fn payload(&self) -> &[u8] {
+        self.ser_msg.return_read_data()
+    }
+
+    /// Returns the id of the message completed by [NbTransfer.poll()][poll]
+    ///
+    /// [poll]: NbTransfer::poll()
+    pub fn packet_id(&self) -> u8 {
+        self.ser_msg.return_msg_id()
+    }
+}
+
+impl<U: serial::Read<u8>> NbTransfer<U> {
+    /// Reads and parses bytes until a message is completed or no byte is available,
+    /// like `available()` of the Arduino library. Returns true if a message was completed, its
+    /// payload and id are then returned by [NbTransfer.payload()][payload] and
+    /// [NbTransfer.packet_id()][packet_id] until this function is called again.
+    ///
+    /// [payload]: NbTransfer::payload()
+    /// [packet_id]: NbTransfer::packet_id()
+    pub fn poll(&mut self) -> Result<bool, TransferError<U::Error>> {
+        loop {
+            // The bytes replayed by the resync are parsed before reading the next byte,
+            // so every byte read is parsed completely
+            while self.ser_msg.has_resync_bytes() {
+                if self.ser_msg.parse(&mut &[][..])?.is_some() {
+                    return Ok(true);
+                }
+            }
+            let val = match self.port.read() {
+                Ok(val) => val,
+                Err(nb::Error::WouldBlock) => return Ok(false),
+                Err(nb::Error::Other(e)) => return Err(TransferError::Port(e)),
+            };
+            if self.ser_msg.parse(&mut &[val][..])?.is_some() {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+impl<U: serial::Write<u8>> NbTransfer<U> {
+    /// Packs the slice into the message format and writes the message to the port byte by byte,
+    /// blocking until the port accepted all bytes, like `sendData()` of the Arduino library
+    pub fn send(&mut self, data: &[u8], id: u8) -> Result<(), TransferError<U::Error>> {
+        for val in self.ser_msg.config().encode_slices_iter(&[data], id)? {
+            nb::block!(self.port.write(val)).map_err(TransferError::Port)?;
+        }
+        nb::block!(self.port.flush()).map_err(TransferError::Port)
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="zerocopy")] {
+        use zerocopy::{AsBytes, FromBytes};
+
+        impl<U: Write> EmbeddedTransfer<U> {
+            /// Writes the object as the payload of a message to the port,
+            /// like `sendDatum()` of the Arduino library
+            pub fn send_obj<O: AsBytes + ?Sized>(
+                &mut self,
+                obj: &O,
+                id: u8,
+            ) -> Result<(), TransferError<U::Error>> {
+                self.send(obj.as_bytes(), id)
+            }
+        }
+
+        impl<U> EmbeddedTransfer<U> {
+            /// Reads an object starting at `index` from the payload of the message completed by
+            /// [EmbeddedTransfer.poll()][poll], like `rxObj()` of the Arduino library
+            ///
+            /// [poll]: EmbeddedTransfer::poll()
+            pub fn rx_obj<O: FromBytes>(&self, index: usize) -> Result<O, Error> {
+                self.ser_msg.payload_reader().get_at(index)
+            }
+        }
+
+        impl<U: serial::Write<u8>> NbTransfer<U> {
+            /// Writes the object as the payload of a message to the port,
+            /// like `sendDatum()` of the Arduino library
+            pub fn send_obj<O: AsBytes + ?Sized>(
+                &mut self,
+                obj: &O,
+                id: u8,
+            ) -> Result<(), TransferError<U::Error>> {
+                self.send(obj.as_bytes(), id)
+            }
+        }
+
+        impl<U> NbTransfer<U> {
+            /// Reads an object starting at `index` from the payload of the message completed by
+            /// [NbTransfer.poll()][poll], like `rxObj()` of the Arduino library
+            ///
+            /// [poll]: NbTransfer::poll()
+            pub fn rx_obj<O: FromBytes>(&self, index: usize) -> Result<O, Error> {
+                self.ser_msg.payload_reader().get_at(index)
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Creating messages byte by byte without a buffer.
+
+use core::iter::{Copied, Flatten};
+use core::slice::Iter;
+
+use crate::crc::{self, Crc};
+use crate::{Crc8, Error, FramingConfig, SerMsg};
+
+type PayloadBytes<'p, 'd> = Copied<Flatten<Copied<Iter<'p, &'d [u8]>>>>;
+
+/// Iterator over the bytes of a message, returned by [SerMsg::encode_slices_iter()][encode_slices_iter]
+/// and [FramingConfig.encode_slices_iter_crc()][encode_slices_iter_crc].
+///
+/// The Consistent Overhead Byte Stuffing and the CRC are calculated while iterating,
+/// so the message never has to be stored as a whole.
+///
+/// [encode_slices_iter]: SerMsg::encode_slices_iter()
+/// [encode_slices_iter_crc]: FramingConfig::encode_slices_iter_crc()
+#[derive(Debug, Clone)]
+pub struct EncodeIter<'p, 'd, C = Crc8> {
+    payload: PayloadBytes<'p, 'd>,
+    config: FramingConfig,
+    id: u8,
+    cobs_byte: u8,
+    payload_len: usize,
+    crc: C,
+    ind: usize,
+}
+
+impl<C: Crc> Iterator for EncodeIter<'_, '_, C> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let val = match self.ind {
+            0 => self.config.start_byte(),
+            1 => self.id,
+            2 => self.cobs_byte,
+            3 => self.payload_len as u8,
+            ind if ind < 4 + self.payload_len => {
+                let mut val = self.payload.next()?;
+                if val == self.config.start_byte() {
+                    // Points to the next start byte or is 0 for the last one
+                    val = self
+                        .payload
+                        .clone()
+                        .position(|val| val == self.config.start_byte())
+                        .map_or(0, |pos| pos as u8 + 1);
+                }
+                self.crc.update(&[val]);
+                val
+            }
+            ind if ind < 4 + self.payload_len + crc::len::<C>() => {
+                // The CRC is sent with its most significant byte first
+                let crc_ind = ind - 4 - self.payload_len;
+                self.crc.finish().to_be_bytes()[4 - crc::len::<C>() + crc_ind]
+            }
+            ind if ind == 4 + self.payload_len + crc::len::<C>() => self.config.stop_byte(),
+            _ => return None,
+        };
+        self.ind += 1;
+        Some(val)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let msg_len = SerMsg::encoded_len_crc::<C>(self.payload_len);
+        let remaining = msg_len - self.ind.min(msg_len);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<C: Crc> ExactSizeIterator for EncodeIter<'_, '_, C> {}
+
+impl SerMsg {
+    /// Returns an iterator over the bytes of the message whose payload is the concatenation
+    /// of all slices in `parts`. Useful to write the message byte by byte, e.g. directly into
+    /// the data register of a UART, without storing it in a buffer first.
+    /// Returns [PayloadTooLarge][PayloadTooLarge] if the slices exceed the maximum
+    /// supported length of 254 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::SerMsg;
+    ///
+    /// let header = [1, 126];
+    /// let samples = [3, 126, 5];
+    /// let msg_bytes: Vec<u8> = SerMsg::encode_slices_iter(&[&header, &samples], 1)
+    ///     .unwrap()
+    ///     .collect();
+    /// assert_eq!(msg_bytes, SerMsg::encode_vec(&[1, 126, 3, 126, 5], 1).unwrap());
+    /// ```
+    ///
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    pub fn encode_slices_iter<'p, 'd>(
+        parts: &'p [&'d [u8]],
+        id: u8,
+    ) -> Result<EncodeIter<'p, 'd>, Error> {
+        FramingConfig::DEFAULT.encode_slices_iter(parts, id)
+    }
+}
+
+impl FramingConfig {
+    /// Same as [SerMsg::encode_slices_iter()][encode_slices_iter] with this configuration.
+    /// Returns [PayloadTooLarge][PayloadTooLarge] if the slices exceed the maximum payload length.
+    ///
+    /// [encode_slices_iter]: SerMsg::encode_slices_iter()
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    pub fn encode_slices_iter<'p, 'd>(
+        &self,
+        parts: &'p [&'d [u8]],
+        id: u8,
+    ) -> Result<EncodeIter<'p, 'd>, Error> {
+        self.encode_slices_iter_crc(Crc8::new(), parts, id)
+    }
+
+    /// Same as [FramingConfig.encode_slices_iter()][encode_slices_iter], but the CRC is
+    /// calculated with the [Crc][Crc] `crc`.
+    ///
+    /// [encode_slices_iter]: FramingConfig::encode_slices_iter()
+    /// [Crc]: Crc
+    pub fn encode_slices_iter_crc<'p, 'd, C: Crc>(
+        &self,
+        mut crc: C,
+        parts: &'p [&'d [u8]],
+        id: u8,
+    ) -> Result<EncodeIter<'p, 'd, C>, Error> {
+        crc.reset();
+        let payload_len = self.slices_len(parts)?;
+        let payload = parts.iter().copied().flatten().copied();
+        let cobs_byte = payload
+            .clone()
+            .position(|val| val == self.start_byte())
+            .map_or(0xFF, |pos| pos as u8);
+        Ok(EncodeIter {
+            payload,
+            config: *self,
+            id,
+            cobs_byte,
+            payload_len,
+            crc,
+            ind: 0,
+        })
+    }
+}
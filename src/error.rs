@@ -0,0 +1,165 @@
+//! The error type of this crate.
+
+use core::fmt;
+
+/// The errors that can occur when creating or parsing messages.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{Error, SerMsg};
+///
+/// let too_long_data = [0; 255];
+/// match SerMsg::encode_vec(&too_long_data, 1) {
+///     Ok(_msg) => (),
+///     Err(Error::PayloadTooLarge { len }) => println!("Can't send {} bytes", len),
+///     Err(e) => println!("Error: {}", e),
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The data exceeds the maximum payload length of 254 bytes or the one of the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: crate::FramingConfig
+    PayloadTooLarge {
+        /// The length of the data
+        len: usize,
+    },
+    /// The buffer is too small to hold the message
+    BufferTooSmall {
+        /// The length of the buffer
+        len: usize,
+        /// The length of the message
+        needed: usize,
+    },
+    /// Reading from a payload exceeded its length
+    OutOfBounds {
+        /// The index of the first byte to read
+        index: usize,
+        /// The amount of bytes to read
+        len: usize,
+        /// The length of the payload
+        payload_len: usize,
+    },
+    /// The first byte of a complete message is not the start byte
+    StartByte {
+        /// The byte found instead of the start byte
+        found: u8,
+    },
+    /// The length of the slice doesn't match the length of a complete message
+    FrameLength {
+        /// The length of the slice
+        len: usize,
+    },
+    /// The received payload length exceeded its maximum of 254 or the one of the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: crate::FramingConfig
+    HighPayload {
+        /// The received payload length
+        len: u8,
+    },
+    /// The CrcCheck failed, the message is most likely corrupted
+    Crc {
+        /// The CRC calculated from the received payload
+        expected: u32,
+        /// The received CRC
+        received: u32,
+    },
+    /// The StopByte was not found after the CrcCheck
+    StopByte {
+        /// The byte found instead of the stop byte
+        found: u8,
+    },
+    /// Couldn't successfully unpack the Consistent Overhead Byte Stuffing
+    Cobs,
+    /// The message was not completed within the configured timeout and was dropped
+    Stale,
+    /// The queue of a [QueuedSerMsg][QueuedSerMsg], [RxProducer][RxProducer] or
+    /// [TxQueue][TxQueue] has no room for the message
+    ///
+    /// [QueuedSerMsg]: crate::QueuedSerMsg
+    /// [RxProducer]: crate::RxProducer
+    /// [TxQueue]: crate::TxQueue
+    QueueFull,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::PayloadTooLarge { len } => {
+                write!(
+                    f,
+                    "payload of {} bytes exceeds the maximum payload length",
+                    len
+                )
+            }
+            Error::BufferTooSmall { len, needed } => write!(
+                f,
+                "buffer of {} bytes is too small for a message of {} bytes",
+                len, needed
+            ),
+            Error::OutOfBounds {
+                index,
+                len,
+                payload_len,
+            } => write!(
+                f,
+                "reading {} bytes at index {} exceeds the payload of {} bytes",
+                len, index, payload_len
+            ),
+            Error::StartByte { found } => {
+                write!(f, "expected start byte, found {:#04x}", found)
+            }
+            Error::FrameLength { len } => {
+                write!(f, "{} bytes don't match the length of the message", len)
+            }
+            Error::HighPayload { len } => {
+                write!(f, "payload length {} exceeds the maximum", len)
+            }
+            Error::Crc { expected, received } => write!(
+                f,
+                "CRC mismatch, expected {:#04x}, received {:#04x}",
+                expected, received
+            ),
+            Error::StopByte { found } => {
+                write!(f, "expected stop byte, found {:#04x}", found)
+            }
+            Error::Cobs => write!(f, "couldn't unpack the consistent overhead byte stuffing"),
+            Error::Stale => write!(f, "message was not completed within the timeout"),
+            Error::QueueFull => write!(f, "the queue is full"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+/// The errors of the transfers over the traits of embedded HALs, e.g. `EmbeddedTransfer` of the
+/// `embedded-hal` feature, wrapping the error `E` of the port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferError<E> {
+    /// Reading from or writing to the port failed
+    Port(E),
+    /// The port reached its end before a message was completed
+    Eof,
+    /// The message couldn't be created or the received message failed to parse
+    Message(Error),
+}
+
+impl<E> From<Error> for TransferError<E> {
+    fn from(e: Error) -> Self {
+        TransferError::Message(e)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Display for TransferError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferError::Port(e) => write!(f, "port error: {:?}", e),
+            TransferError::Eof => write!(f, "the port reached its end"),
+            TransferError::Message(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for TransferError<E> {}
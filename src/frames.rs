@@ -0,0 +1,148 @@
+//! Walking all messages contained in a slice of received bytes.
+
+use crate::{Crc, Crc8, Error, Message, SerMsgBase};
+
+/// A successfully parsed message borrowed from the [SerMsg][SerMsg] that parsed it.
+///
+/// [SerMsg]: crate::SerMsg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameRef<'a> {
+    /// The packet id of the message
+    pub id: u8,
+    /// The unpacked payload of the message
+    pub payload: &'a [u8],
+}
+
+/// Parses all messages of a slice of bytes, returned by [SerMsg.frames()][frames].
+///
+/// [Frames][Frames] is an [Iterator] returning owned [Messages][Message]. To avoid copying the
+/// payload, [Frames.next_frame()][next_frame] returns the messages borrowed from the
+/// [SerMsg][SerMsg] one after another instead.
+///
+/// [frames]: crate::SerMsg::frames()
+/// [SerMsg]: crate::SerMsg
+/// [next_frame]: Frames::next_frame()
+/// [Frames]: Frames
+/// [Message]: Message
+pub struct Frames<'m, 'b, B = [u8; 254], C = Crc8> {
+    ser_msg: &'m mut SerMsgBase<B, C>,
+    bytes: &'b [u8],
+    parsed_bytes: usize,
+}
+
+impl<'m, 'b, B: AsRef<[u8]> + AsMut<[u8]>, C: Crc> Frames<'m, 'b, B, C> {
+    /// Parses bytes until the next message is complete or an error occurred.
+    /// Returns None when all bytes were parsed. Bytes of an incomplete message at the end of
+    /// the slice are kept in the [SerMsg][SerMsg] and completed by the next parsed bytes.
+    ///
+    /// [SerMsg]: crate::SerMsg
+    pub fn next_frame(&mut self) -> Option<Result<FrameRef<'_>, Error>> {
+        loop {
+            if self.bytes.is_empty() && !self.ser_msg.has_resync_bytes() {
+                return None;
+            }
+            let bytes_len = self.bytes.len();
+            let result = self
+                .ser_msg
+                .parse(&mut self.bytes)
+                .map(|frame| frame.is_some());
+            self.parsed_bytes += bytes_len - self.bytes.len();
+            match result {
+                Ok(false) => (),
+                Ok(true) => return Some(Ok(self.ser_msg.read_frame())),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Returns the amount of bytes of the slice that have been parsed so far
+    pub fn parsed_bytes(&self) -> usize {
+        self.parsed_bytes
+    }
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>, C: Crc> SerMsgBase<B, C> {
+    /// Returns a [Frames][Frames] walking over all messages and errors in the slice.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::SerMsg;
+    ///
+    /// let mut rcvd_bytes = SerMsg::create_msg_vec(&[1, 2], 1).unwrap();
+    /// rcvd_bytes.extend(SerMsg::create_msg_vec(&[3], 2).unwrap());
+    ///
+    /// let mut ser_msg = SerMsg::new();
+    /// let mut frames = ser_msg.frames(&rcvd_bytes);
+    /// while let Some(frame) = frames.next_frame() {
+    ///     match frame {
+    ///         Ok(frame) => println!("ID: {}, Payload: {:?}", frame.id, frame.payload),
+    ///         Err(e) => println!("Error: {}", e),
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [Frames]: Frames
+    pub fn frames<'m, 'b>(&'m mut self, bytes: &'b [u8]) -> Frames<'m, 'b, B, C> {
+        Frames {
+            ser_msg: self,
+            bytes,
+            parsed_bytes: 0,
+        }
+    }
+
+    /// Calls the closure for every message and error in the slice, see [SerMsg.frames()][frames].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::SerMsg;
+    ///
+    /// let mut rcvd_bytes = SerMsg::create_msg_vec(&[1, 2], 1).unwrap();
+    /// rcvd_bytes.extend(SerMsg::create_msg_vec(&[3], 2).unwrap());
+    ///
+    /// let mut ids = Vec::new();
+    /// let mut ser_msg = SerMsg::new();
+    /// ser_msg.for_each_frame(&rcvd_bytes, |frame| {
+    ///     if let Ok(frame) = frame {
+    ///         ids.push(frame.id);
+    ///     }
+    /// });
+    /// assert_eq!(ids, vec![1, 2]);
+    /// ```
+    ///
+    /// [frames]: crate::SerMsg::frames()
+    pub fn for_each_frame<F>(&mut self, bytes: &[u8], mut f: F)
+    where
+        F: FnMut(Result<FrameRef<'_>, Error>),
+    {
+        let mut frames = self.frames(bytes);
+        while let Some(frame) = frames.next_frame() {
+            f(frame);
+        }
+    }
+}
+
+/// Returns owned messages, so the iterator can be used with all iterator adapters.
+/// The messages don't allocate, so this works without the `alloc` feature as well.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{Message, SerMsg};
+///
+/// let mut rcvd_bytes = SerMsg::create_msg_vec(&[1, 2], 1).unwrap();
+/// rcvd_bytes.extend(SerMsg::create_msg_vec(&[3], 2).unwrap());
+///
+/// let mut ser_msg = SerMsg::new();
+/// let msgs: Vec<Message> = ser_msg.frames(&rcvd_bytes).filter_map(Result::ok).collect();
+/// assert_eq!(msgs[1].payload, [3]);
+/// ```
+impl<B: AsRef<[u8]> + AsMut<[u8]>, C: Crc> Iterator for Frames<'_, '_, B, C> {
+    type Item = Result<Message, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame()
+            .map(|frame| frame.map(Message::from_parsed))
+    }
+}
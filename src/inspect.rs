@@ -0,0 +1,364 @@
+//! Checking every field of a message to find out why it is rejected.
+
+use core::fmt::{self, Write};
+
+use crate::crc::{self, Crc};
+use crate::{Crc8, FramingConfig, SerMsg};
+
+/// A problem with one field of a message, found by [inspect_frame()][inspect_frame].
+///
+/// [inspect_frame]: inspect_frame()
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FrameProblem {
+    /// The first byte is not the start byte
+    StartByte {
+        /// The byte found instead of the start byte
+        found: u8,
+    },
+    /// The message is too short to contain all fields
+    TooShort {
+        /// The length of the message
+        len: usize,
+    },
+    /// The payload length exceeds the maximum of the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: FramingConfig
+    HighPayload {
+        /// The payload length of the message
+        len: u8,
+    },
+    /// The length of the message doesn't match its payload length,
+    /// the fields after the payload are located from the end of the message
+    FrameLength {
+        /// The length of the message
+        len: usize,
+        /// The length expected from the payload length
+        expected: usize,
+    },
+    /// A pointer of the Consistent Overhead Byte Stuffing points outside of the payload
+    CobsPointer {
+        /// The index of the payload byte holding the pointer, None for the overhead byte
+        index: Option<usize>,
+        /// The index of the payload byte the pointer points to
+        target: usize,
+    },
+    /// Start bytes in the payload are not replaced by the Consistent Overhead Byte Stuffing
+    UnstuffedStartByte {
+        /// The index of the first start byte in the payload
+        index: usize,
+        /// The amount of start bytes
+        count: usize,
+    },
+    /// The received CRC doesn't match the CRC calculated from the payload
+    Crc {
+        /// The CRC calculated from the received payload
+        expected: u32,
+        /// The received CRC
+        received: u32,
+    },
+    /// The last byte is not the stop byte
+    StopByte {
+        /// The byte found instead of the stop byte
+        found: u8,
+    },
+}
+
+impl fmt::Display for FrameProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameProblem::StartByte { found } => {
+                write!(f, "expected start byte, found {:#04x}", found)
+            }
+            FrameProblem::TooShort { len } => {
+                write!(f, "{} bytes are too short for a message", len)
+            }
+            FrameProblem::HighPayload { len } => {
+                write!(f, "payload length {} exceeds the maximum", len)
+            }
+            FrameProblem::FrameLength { len, expected } => write!(
+                f,
+                "message of {} bytes, the payload length needs {} bytes",
+                len, expected
+            ),
+            FrameProblem::CobsPointer {
+                index: None,
+                target,
+            } => write!(
+                f,
+                "overhead byte points to payload byte {} outside of the payload",
+                target
+            ),
+            FrameProblem::CobsPointer {
+                index: Some(index),
+                target,
+            } => write!(
+                f,
+                "COBS pointer in payload byte {} points to payload byte {} outside of the payload",
+                index, target
+            ),
+            FrameProblem::UnstuffedStartByte { index, count } => write!(
+                f,
+                "{} start bytes in the payload are not stuffed, the first is payload byte {}",
+                count, index
+            ),
+            FrameProblem::Crc { expected, received } => write!(
+                f,
+                "CRC mismatch, expected {:#04x}, received {:#04x}",
+                expected, received
+            ),
+            FrameProblem::StopByte { found } => {
+                write!(f, "expected stop byte, found {:#04x}", found)
+            }
+        }
+    }
+}
+
+/// The result of checking every field of a message, returned by [inspect_frame()][inspect_frame].
+///
+/// Unlike [decode_frame()][decode_frame] all fields are checked, so every problem of the message
+/// is reported. The [Display][fmt::Display] implementation renders the layout of the message
+/// with one annotated byte per line, like the packet anatomy of the crate documentation.
+///
+/// [inspect_frame]: inspect_frame()
+/// [decode_frame]: crate::decode_frame()
+#[derive(Debug, Clone)]
+pub struct FrameReport<'a> {
+    frame: &'a [u8],
+    crc_len: usize,
+    // The payload is only located if the message contains all fields
+    payload_len: Option<usize>,
+    cobs_pointers: [bool; 254],
+    problems: heapless::Vec<FrameProblem, 8>,
+}
+
+impl<'a> FrameReport<'a> {
+    /// Returns true if no problem was found
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Returns all problems found, ordered by the fields of the message
+    pub fn problems(&self) -> &[FrameProblem] {
+        &self.problems
+    }
+
+    /// Returns the inspected message
+    pub fn frame(&self) -> &'a [u8] {
+        self.frame
+    }
+
+    fn add(&mut self, problem: FrameProblem) {
+        // There is at most one problem of each kind
+        self.problems.push(problem).unwrap();
+    }
+
+    fn label(&self, ind: usize) -> Result<heapless::String<40>, fmt::Error> {
+        let mut label = heapless::String::new();
+        match (ind, self.payload_len) {
+            (0, _) => write!(label, "Start byte"),
+            (1, _) => write!(label, "Packet ID"),
+            (2, _) => write!(label, "COBS Overhead byte"),
+            (3, _) => write!(label, "# of payload bytes"),
+            (_, None) => write!(label, "Unknown"),
+            (ind, Some(payload_len)) if ind < 4 + payload_len => {
+                if self.cobs_pointers[ind - 4] {
+                    write!(label, "Payload byte {} (COBS pointer)", ind - 4)
+                } else {
+                    write!(label, "Payload byte {}", ind - 4)
+                }
+            }
+            (ind, _) if ind == self.frame.len() - 1 => write!(label, "Stop byte"),
+            (ind, Some(payload_len)) => write!(
+                label,
+                "CRC byte {} of {}",
+                ind - 3 - payload_len,
+                self.crc_len
+            ),
+        }?;
+        Ok(label)
+    }
+
+    // Returns the index of the byte the problem belongs to
+    fn problem_ind(&self, problem: &FrameProblem) -> Option<usize> {
+        match problem {
+            FrameProblem::StartByte { .. } => Some(0),
+            FrameProblem::TooShort { .. } => None,
+            FrameProblem::HighPayload { .. } | FrameProblem::FrameLength { .. } => Some(3),
+            FrameProblem::CobsPointer { index: None, .. } => Some(2),
+            FrameProblem::CobsPointer {
+                index: Some(index), ..
+            } => Some(4 + index),
+            FrameProblem::UnstuffedStartByte { index, .. } => Some(4 + index),
+            FrameProblem::Crc { .. } => self.payload_len.map(|payload_len| 4 + payload_len),
+            FrameProblem::StopByte { .. } => Some(self.frame.len() - 1),
+        }
+    }
+}
+
+impl fmt::Display for FrameReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (ind, val) in self.frame.iter().enumerate() {
+            let label = self.label(ind)?;
+            let mut problems = self
+                .problems
+                .iter()
+                .filter(|problem| self.problem_ind(problem) == Some(ind));
+            match problems.next() {
+                // Aligns the problems behind the longest label
+                Some(problem) => write!(f, "{:08b} {:<36} <- {}", val, label, problem)?,
+                None => write!(f, "{:08b} {}", val, label)?,
+            }
+            for problem in problems {
+                write!(f, ", {}", problem)?;
+            }
+            writeln!(f)?;
+        }
+        for problem in self.problems.iter() {
+            if self.problem_ind(problem).is_none() {
+                writeln!(f, "{}", problem)?;
+            }
+        }
+        match self.problems.len() {
+            0 => write!(f, "no problems found"),
+            1 => write!(f, "1 problem found"),
+            len => write!(f, "{} problems found", len),
+        }
+    }
+}
+
+/// Checks every field of a slice containing exactly one complete message and reports all
+/// problems found, to find out why a message is rejected.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{inspect_frame, FrameProblem, SerMsg};
+///
+/// let mut send_msg = SerMsg::encode_vec(&[1, 126, 3], 2).unwrap();
+/// send_msg[0] = 0;
+/// send_msg[8] = 0;
+///
+/// let report = inspect_frame(&send_msg);
+/// assert_eq!(
+///     report.problems(),
+///     &[
+///         FrameProblem::StartByte { found: 0 },
+///         FrameProblem::StopByte { found: 0 },
+///     ]
+/// );
+/// print!("{}", report);
+/// ```
+///
+/// Prints:
+///
+/// ```text
+/// 00000000 Start byte                           <- expected start byte, found 0x00
+/// 00000010 Packet ID
+/// 00000001 COBS Overhead byte
+/// 00000011 # of payload bytes
+/// 00000001 Payload byte 0
+/// 00000000 Payload byte 1 (COBS pointer)
+/// 00000011 Payload byte 2
+/// 01101000 CRC byte 1 of 1
+/// 00000000 Stop byte                            <- expected stop byte, found 0x00
+/// 2 problems found
+/// ```
+pub fn inspect_frame(frame: &[u8]) -> FrameReport<'_> {
+    FramingConfig::DEFAULT.inspect_frame(frame)
+}
+
+impl FramingConfig {
+    /// Same as [inspect_frame()][inspect_frame] with this configuration.
+    ///
+    /// [inspect_frame]: inspect_frame()
+    pub fn inspect_frame<'a>(&self, frame: &'a [u8]) -> FrameReport<'a> {
+        self.inspect_frame_crc(&mut Crc8::new(), frame)
+    }
+
+    /// Same as [FramingConfig.inspect_frame()][inspect_frame], but the CRC is checked
+    /// with the [Crc][Crc] `crc`.
+    ///
+    /// [inspect_frame]: FramingConfig::inspect_frame()
+    /// [Crc]: Crc
+    pub fn inspect_frame_crc<'a, C: Crc>(&self, crc: &mut C, frame: &'a [u8]) -> FrameReport<'a> {
+        let mut report = FrameReport {
+            frame,
+            crc_len: crc::len::<C>(),
+            payload_len: None,
+            cobs_pointers: [false; 254],
+            problems: heapless::Vec::new(),
+        };
+        if let Some(&found) = frame.first() {
+            if found != self.start_byte() {
+                report.add(FrameProblem::StartByte { found });
+            }
+        }
+        if frame.len() < SerMsg::encoded_len_crc::<C>(0) {
+            report.add(FrameProblem::TooShort { len: frame.len() });
+            return report;
+        }
+
+        let len_byte = frame[3];
+        if len_byte > self.max_payload() {
+            report.add(FrameProblem::HighPayload { len: len_byte });
+        }
+        let expected = SerMsg::encoded_len_crc::<C>(len_byte as usize);
+        if frame.len() != expected {
+            report.add(FrameProblem::FrameLength {
+                len: frame.len(),
+                expected,
+            });
+        }
+        // A message with a wrong length is still checked with the payload between the header
+        // and the CRC, as long as the payload doesn't exceed 254 bytes
+        let payload_len = frame.len() - SerMsg::encoded_len_crc::<C>(0);
+        if payload_len > SerMsg::MAX_PACKET_SIZE as usize {
+            return report;
+        }
+        report.payload_len = Some(payload_len);
+        let payload = &frame[4..4 + payload_len];
+
+        // An overhead byte above 254 means the payload has no COBS pointers
+        if frame[2] <= SerMsg::MAX_PACKET_SIZE {
+            let mut pointer = frame[2] as usize;
+            let mut pointer_ind = None;
+            loop {
+                if pointer >= payload_len {
+                    report.add(FrameProblem::CobsPointer {
+                        index: pointer_ind,
+                        target: pointer,
+                    });
+                    break;
+                }
+                report.cobs_pointers[pointer] = true;
+                match payload[pointer] {
+                    0 => break,
+                    delta => {
+                        pointer_ind = Some(pointer);
+                        pointer += delta as usize;
+                    }
+                }
+            }
+        }
+        let mut unstuffed = payload
+            .iter()
+            .enumerate()
+            .filter(|(ind, val)| **val == self.start_byte() && !report.cobs_pointers[*ind]);
+        if let Some((index, _)) = unstuffed.next() {
+            let count = 1 + unstuffed.count();
+            report.add(FrameProblem::UnstuffedStartByte { index, count });
+        }
+
+        let expected = crc.checksum(payload);
+        let received = crc::read_crc::<C>(&frame[4 + payload_len..]);
+        if expected != received {
+            report.add(FrameProblem::Crc { expected, received });
+        }
+        let found = frame[frame.len() - 1];
+        if found != self.stop_byte() {
+            report.add(FrameProblem::StopByte { found });
+        }
+        report
+    }
+}
@@ -12,7 +12,7 @@
 //! - uses start and stop bytes
 //! - uses packet ids
 //! - uses consistent overhead byte stuffing
-//! - uses CRC-8 (Polynomial 0x9B with lookup table)
+//! - uses CRC-8 (Polynomial 0x9B with lookup table), other CRCs can be used with the [Crc] trait
 //! - allows the use of dynamically sized packets (packets can have payload lengths anywhere from 0 to 254 bytes)
 //! - can transfer bytes, ints, floats, structs, arrays, vectors
 //!
@@ -92,6 +92,71 @@
 
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "embedded-io-async")]
+mod async_transfer;
+mod builder;
+#[cfg(any(
+    feature = "std",
+    feature = "embedded-hal",
+    feature = "embedded-io-async"
+))]
+mod chunks;
+mod clock;
+#[cfg(feature = "tokio")]
+mod codec;
+mod config;
+mod crc;
+mod decode;
+#[cfg(feature = "embedded-hal")]
+mod embedded;
+mod encode;
+mod error;
+mod frames;
+mod inspect;
+#[cfg(feature = "std")]
+mod link;
+mod message;
+mod queue;
+mod reader;
+mod rx;
+mod stats;
+#[cfg(feature = "std")]
+mod transfer;
+mod tx;
+
+#[cfg(feature = "embedded-io-async")]
+pub use async_transfer::AsyncTransfer;
+pub use builder::FrameBuilder;
+#[cfg(feature = "std")]
+pub use clock::StdClock;
+pub use clock::{Clock, TickClock};
+#[cfg(feature = "tokio")]
+pub use codec::{OnFrameError, SerMsgCodec};
+pub use config::FramingConfig;
+pub use crc::{crc16_table, crc32_table, crc8_table, Crc, Crc16, Crc32, Crc8};
+pub use decode::{decode_frame, decode_frame_in_place, Payload, PayloadIter};
+#[cfg(feature = "embedded-hal")]
+pub use embedded::{EmbeddedTransfer, NbTransfer};
+pub use encode::EncodeIter;
+pub use error::{Error, TransferError};
+pub use frames::{FrameRef, Frames};
+pub use inspect::{inspect_frame, FrameProblem, FrameReport};
+#[cfg(feature = "std")]
+pub use link::{LinkHandle, LinkSender};
+pub use message::Message;
+#[cfg(feature = "alloc")]
+pub use queue::FrameDeque;
+pub use queue::{FrameRing, FrameStore, Overflow, QueueCounters, QueuedFrame, QueuedSerMsg};
+pub use reader::PayloadReader;
+pub use rx::{RxConsumer, RxProducer, RxQueue};
+pub use stats::LinkStats;
+#[cfg(feature = "std")]
+pub use transfer::Transfer;
+pub use tx::TxQueue;
+
 /// Shows the progress/error when parsing bytes with [SerMsg.parse_read_bytes()][parse_read_bytes].
 ///
 /// # Example
@@ -112,11 +177,12 @@
 ///     ParseState::HighPayloadError => (),
 ///     ParseState::StopByteError => (),
 ///     ParseState::COBSError => (),
+///     ParseState::StalePacketError => (),
 /// }
 /// ```
 ///
 /// [parse_read_bytes]: SerMsg::parse_read_bytes()
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ParseState {
     /// The bytes were handled successfully, but no complete message is ready
     Continue,
@@ -124,174 +190,285 @@ pub enum ParseState {
     DataReady,
     /// The CrcCheck failed, the message is most likely corrupted
     CrcError,
-    /// The Payload length exceeded its maximum of 254
+    /// The Payload length exceeded its maximum of 254 or the one of the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: FramingConfig
     HighPayloadError,
     /// The StopByte was not found after the CrcCheck
     StopByteError,
     /// Couldn't successfully unpack the Consistent Overhead Byte Stuffing
     COBSError,
+    /// The message was not completed within the configured timeout and was dropped,
+    /// see [SerMsg.set_timeout()][set_timeout]
+    ///
+    /// [set_timeout]: SerMsg::set_timeout()
+    StalePacketError,
+}
+
+impl ParseState {
+    fn from_result(result: Result<bool, Error>) -> ParseState {
+        match result {
+            Ok(false) => ParseState::Continue,
+            Ok(true) => ParseState::DataReady,
+            Err(Error::Crc { .. }) => ParseState::CrcError,
+            Err(Error::HighPayload { .. }) => ParseState::HighPayloadError,
+            Err(Error::StopByte { .. }) => ParseState::StopByteError,
+            Err(Error::Cobs) => ParseState::COBSError,
+            Err(Error::Stale) => ParseState::StalePacketError,
+            Err(e) => unreachable!("{} is not a parse error", e),
+        }
+    }
 }
 
-enum FindByte {
+/// The field of a message the parser expects next, returned by [SerMsg.state()][state]
+///
+/// [state]: SerMsg::state()
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FindByte {
+    /// Searching for the start byte of the next message
     Start,
+    /// Waiting for the packet id
     Id,
+    /// Waiting for the COBS overhead byte
     Overhead,
+    /// Waiting for the payload length
     PayloadLen,
+    /// Receiving the payload
     Payload,
+    /// Receiving the CRC
     Crc,
+    /// Waiting for the stop byte
     End,
 }
 
 /// Struct that implements all the functionality of this crate
-pub struct SerMsg {
+///
+/// Parses messages with a payload of up to `N` bytes, which is the maximum of 254 by default.
+/// Messages with a longer payload are rejected with a [HighPayloadError][HighPayloadError],
+/// so the memory of the parser can be reduced to the messages actually used.
+/// The CRC of the parsed messages is checked with `C`, which is the [Crc8][Crc8] of the Arduino
+/// SerialTransfer library by default, see [SerMsg::with_crc()][with_crc].
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{ParseState, SerMsg};
+///
+/// let mut ser_msg: SerMsg<32> = SerMsg::default();
+///
+/// let send_msg = SerMsg::encode_vec(&[0; 32], 1).unwrap();
+/// let (parse_state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+/// assert!(matches!(parse_state, ParseState::DataReady));
+///
+/// let send_msg = SerMsg::encode_vec(&[0; 33], 1).unwrap();
+/// let (parse_state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+/// assert!(matches!(parse_state, ParseState::HighPayloadError));
+/// ```
+///
+/// [HighPayloadError]: ParseState::HighPayloadError
+/// [Crc8]: Crc8
+/// [with_crc]: SerMsg::with_crc()
+pub type SerMsg<const N: usize = 254, C = Crc8> = SerMsgBase<[u8; N], C>;
+
+/// [SerMsg][SerMsg] parsing into buffers borrowed from the caller instead of its own arrays.
+///
+/// The length of the buffer is the payload capacity of the parser, see [SerMsgRef::new()][new].
+///
+/// [SerMsg]: SerMsg
+/// [new]: SerMsgRef::new()
+pub type SerMsgRef<'a, C = Crc8> = SerMsgBase<&'a mut [u8], C>;
+
+/// The parser behind [SerMsg][SerMsg] and [SerMsgRef][SerMsgRef], generic over the buffer type `B`.
+///
+/// [SerMsg]: SerMsg
+/// [SerMsgRef]: SerMsgRef
+pub struct SerMsgBase<B, C = Crc8> {
+    config: FramingConfig,
+    crc: C,
     msg_state: FindByte,
     payload_len: u8,
     cobs_byte: u8,
     rcvd_id: u8,
-    rcvd_data: [u8; 254],
+    rcvd_data: B,
     rcvd_ind: usize,
+    rcvd_crc: u32,
+    crc_ind: usize,
+    rcvd_stop: u8,
+    // The amount of bytes received of the current message, which are kept after an error
+    // until the next message starts
+    frame_len: usize,
+    // The amount of input bytes parsed, without the resync bytes parsed again
+    rcvd_bytes: u64,
+    frame_offset: u64,
+    // The amount of input bytes parsed while searching the start byte, which didn't start a message
+    discarded_bytes: u64,
+    // The index of the next payload byte replaced by the overhead byte stuffing, the bytes are
+    // unpacked as they arrive. A start byte inside the payload can't be told apart from an
+    // unpacked one, so after it the payload is stored as received and unpacked at the end.
+    next_cobs_ind: Option<usize>,
+    raw_payload: bool,
+    timeout_ms: Option<u32>,
+    packet_start: u32,
+    resync: bool,
+    // Holds the bytes of a failed message after its start byte, which are at most
+    // 5 bytes and the payload plus a 4 byte CRC. The bytes exceeding the resync
+    // buffer are stored in resync_tail.
+    resync_data: B,
+    resync_tail: [u8; 8],
+    resync_ind: usize,
+    resync_len: usize,
 }
 
-impl Default for SerMsg {
+impl<const N: usize, C: Crc + Default> Default for SerMsg<N, C> {
     fn default() -> Self {
-        Self::new()
+        Self::with_capacity(FramingConfig::DEFAULT, C::default())
     }
 }
 
 impl SerMsg {
-    const START_BYTE: u8 = 126;
-    const STOP_BYTE: u8 = 129;
     const MAX_PACKET_SIZE: u8 = 254;
 
-    const LOOKUP_TABLE: [u8; 256] = [
-        0x00, 0x9b, 0xad, 0x36, 0xc1, 0x5a, 0x6c, 0xf7, 0x19, 0x82, 0xb4, 0x2f, 0xd8, 0x43, 0x75,
-        0xee, 0x32, 0xa9, 0x9f, 0x04, 0xf3, 0x68, 0x5e, 0xc5, 0x2b, 0xb0, 0x86, 0x1d, 0xea, 0x71,
-        0x47, 0xdc, 0x64, 0xff, 0xc9, 0x52, 0xa5, 0x3e, 0x08, 0x93, 0x7d, 0xe6, 0xd0, 0x4b, 0xbc,
-        0x27, 0x11, 0x8a, 0x56, 0xcd, 0xfb, 0x60, 0x97, 0x0c, 0x3a, 0xa1, 0x4f, 0xd4, 0xe2, 0x79,
-        0x8e, 0x15, 0x23, 0xb8, 0xc8, 0x53, 0x65, 0xfe, 0x09, 0x92, 0xa4, 0x3f, 0xd1, 0x4a, 0x7c,
-        0xe7, 0x10, 0x8b, 0xbd, 0x26, 0xfa, 0x61, 0x57, 0xcc, 0x3b, 0xa0, 0x96, 0x0d, 0xe3, 0x78,
-        0x4e, 0xd5, 0x22, 0xb9, 0x8f, 0x14, 0xac, 0x37, 0x01, 0x9a, 0x6d, 0xf6, 0xc0, 0x5b, 0xb5,
-        0x2e, 0x18, 0x83, 0x74, 0xef, 0xd9, 0x42, 0x9e, 0x05, 0x33, 0xa8, 0x5f, 0xc4, 0xf2, 0x69,
-        0x87, 0x1c, 0x2a, 0xb1, 0x46, 0xdd, 0xeb, 0x70, 0x0b, 0x90, 0xa6, 0x3d, 0xca, 0x51, 0x67,
-        0xfc, 0x12, 0x89, 0xbf, 0x24, 0xd3, 0x48, 0x7e, 0xe5, 0x39, 0xa2, 0x94, 0x0f, 0xf8, 0x63,
-        0x55, 0xce, 0x20, 0xbb, 0x8d, 0x16, 0xe1, 0x7a, 0x4c, 0xd7, 0x6f, 0xf4, 0xc2, 0x59, 0xae,
-        0x35, 0x03, 0x98, 0x76, 0xed, 0xdb, 0x40, 0xb7, 0x2c, 0x1a, 0x81, 0x5d, 0xc6, 0xf0, 0x6b,
-        0x9c, 0x07, 0x31, 0xaa, 0x44, 0xdf, 0xe9, 0x72, 0x85, 0x1e, 0x28, 0xb3, 0xc3, 0x58, 0x6e,
-        0xf5, 0x02, 0x99, 0xaf, 0x34, 0xda, 0x41, 0x77, 0xec, 0x1b, 0x80, 0xb6, 0x2d, 0xf1, 0x6a,
-        0x5c, 0xc7, 0x30, 0xab, 0x9d, 0x06, 0xe8, 0x73, 0x45, 0xde, 0x29, 0xb2, 0x84, 0x1f, 0xa7,
-        0x3c, 0x0a, 0x91, 0x66, 0xfd, 0xcb, 0x50, 0xbe, 0x25, 0x13, 0x88, 0x7f, 0xe4, 0xd2, 0x49,
-        0x95, 0x0e, 0x38, 0xa3, 0x54, 0xcf, 0xf9, 0x62, 0x8c, 0x17, 0x21, 0xba, 0x4d, 0xd6, 0xe0,
-        0x7b,
-    ];
-
     /// Returns a new SerMsg instance for parsing read data
     pub fn new() -> SerMsg {
-        let msg_state = FindByte::Start;
-        let rcvd_id: u8 = 0;
-        let cobs_byte: u8 = 0;
-        let payload_len: u8 = 0;
-        let rcvd_data: [u8; 254] = [0; 254];
-        let rcvd_ind: usize = 0;
-
-        SerMsg {
-            msg_state,
-            rcvd_id,
-            cobs_byte,
-            payload_len,
-            rcvd_data,
-            rcvd_ind,
-        }
+        SerMsg::with_config(FramingConfig::DEFAULT)
     }
 
-    /// Returns the data of the parsed message, should only be used after the [ParseState][ParseState] is [DataReady][DataReady]
+    /// Returns a new SerMsg instance for parsing messages using the [FramingConfig][FramingConfig]
     ///
-    /// [ParseState]: ParseState
-    /// [DataReady]: ParseState::DataReady
-    pub fn return_read_data(&self) -> &[u8] {
-        &self.rcvd_data[..self.rcvd_ind]
+    /// [FramingConfig]: FramingConfig
+    pub fn with_config(config: FramingConfig) -> SerMsg {
+        SerMsg::with_crc(config, Crc8::new())
     }
 
-    /// Returns the id of the parsed message, should only be used after the [ParseState][ParseState] is [DataReady][DataReady]
+    /// no_std function to create a message.
+    /// Packs the slice into the message format and returns an array with a fixed length
+    /// of the maximum message size and the last index of the relevant bytes in the array.
+    /// Returns None if the input slice exceeds the maximum supported length of 254 bytes,
+    /// see [SerMsg::encode_arr()][encode_arr] for the reason of the failure.
     ///
-    /// [ParseState]: ParseState
-    /// [DataReady]: ParseState::DataReady
-    pub fn return_msg_id(&self) -> u8 {
-        self.rcvd_id
+    /// [encode_arr]: SerMsg::encode_arr()
+    pub fn create_msg_arr(data: &[u8], id: u8) -> Option<([u8; 260], usize)> {
+        SerMsg::encode_arr(data, id).ok()
     }
 
     /// no_std function to create a message.
     /// Packs the slice into the message format and returns an array with a fixed length
     /// of the maximum message size and the last index of the relevant bytes in the array.
-    /// Returns None if the input slice exceeds the maximum supported length of 254 bytes.
-    pub fn create_msg_arr(data: &[u8], id: u8) -> Option<([u8; 260], usize)> {
-        if data.len() > SerMsg::MAX_PACKET_SIZE as usize {
-            return None;
-        }
+    /// Returns [PayloadTooLarge][PayloadTooLarge] if the input slice exceeds the maximum
+    /// supported length of 254 bytes.
+    ///
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    pub fn encode_arr(data: &[u8], id: u8) -> Result<([u8; 260], usize), Error> {
         let mut msg: [u8; 260] = [0; 260];
-        msg[0] = SerMsg::START_BYTE;
-        msg[1] = id;
-        msg[3] = data.len() as u8;
-        for (i, &val) in data.iter().enumerate() {
-            msg[i + 4] = val;
-        }
-        msg[2] = SerMsg::pack_cobs(&mut msg[4..4 + data.len()]);
-        msg[4 + data.len()] = SerMsg::retrieve_crc(&msg[4..4 + data.len()]);
-        msg[5 + data.len()] = SerMsg::STOP_BYTE;
-        Some((msg, data.len() + 6))
+        let msg_len = SerMsg::encode_into(data, id, &mut msg)?;
+        Ok((msg, msg_len))
     }
 
-    /// Parses the bytes of the input slice. Returns a [ParseState][ParseState] and the amount of bytes parsed
-    /// when an error occured, a complete message was parsed or when all bytes were read.
+    /// Packs the slice into the message format and writes the message to the beginning of `out`.
+    /// Returns the length of the message, which is [SerMsg::encoded_len()][encoded_len] of the slice length.
+    /// Returns [PayloadTooLarge][PayloadTooLarge] if the input slice exceeds the maximum
+    /// supported length of 254 bytes and [BufferTooSmall][BufferTooSmall] if the message doesn't fit into `out`.
     ///
-    /// [ParseState]: ParseState
-    pub fn parse_read_bytes(&mut self, arr: &[u8]) -> (ParseState, usize) {
-        for (i, val) in arr.iter().enumerate() {
-            let state = self.parse_byte(*val);
-            match state {
-                ParseState::Continue => (),
-                _ => return (state, i + 1),
-            }
-        }
-        (ParseState::Continue, arr.len())
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::SerMsg;
+    ///
+    /// let mut tx_buffer = [0; 64];
+    /// let msg_len = SerMsg::encode_into(&[1, 2, 3], 1, &mut tx_buffer).unwrap();
+    /// assert_eq!(msg_len, SerMsg::encoded_len(3));
+    /// // Send: tx_buffer[..msg_len];
+    ///
+    /// // Appending to an existing Vector
+    /// let mut send_bytes = SerMsg::encode_vec(&[1], 1).unwrap();
+    /// let old_len = send_bytes.len();
+    /// send_bytes.resize(old_len + SerMsg::encoded_len(3), 0);
+    /// SerMsg::encode_into(&[1, 2, 3], 2, &mut send_bytes[old_len..]).unwrap();
+    /// ```
+    ///
+    /// [encoded_len]: SerMsg::encoded_len()
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    /// [BufferTooSmall]: Error::BufferTooSmall
+    pub fn encode_into(data: &[u8], id: u8, out: &mut [u8]) -> Result<usize, Error> {
+        SerMsg::encode_slices_into(&[data], id, out)
     }
 
-    fn retrieve_crc(data_slice: &[u8]) -> u8 {
-        let mut calc_crc = 0;
-        for val in data_slice.iter() {
-            calc_crc = SerMsg::LOOKUP_TABLE[(calc_crc ^ val) as usize];
+    /// Same as [SerMsg::encode_into()][encode_into], but the payload is the concatenation
+    /// of all slices in `parts`. This avoids copying e.g. a header and the data into a temporary
+    /// buffer before creating the message.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::SerMsg;
+    ///
+    /// let header = [1, 2];
+    /// let samples = [3, 4, 5];
+    /// let mut tx_buffer = [0; 64];
+    /// let msg_len = SerMsg::encode_slices_into(&[&header, &samples], 1, &mut tx_buffer).unwrap();
+    /// assert_eq!(
+    ///     &tx_buffer[..msg_len],
+    ///     &SerMsg::encode_vec(&[1, 2, 3, 4, 5], 1).unwrap()[..]
+    /// );
+    /// ```
+    ///
+    /// [encode_into]: SerMsg::encode_into()
+    pub fn encode_slices_into(parts: &[&[u8]], id: u8, out: &mut [u8]) -> Result<usize, Error> {
+        FramingConfig::DEFAULT.encode_slices_into(parts, id, out)
+    }
+
+    /// Returns the length of a message with the given payload length
+    pub const fn encoded_len(payload_len: usize) -> usize {
+        payload_len + 6
+    }
+
+    /// Returns the length of a message with the given payload length using the [Crc][Crc] `C`
+    ///
+    /// [Crc]: Crc
+    pub const fn encoded_len_crc<C: Crc>(payload_len: usize) -> usize {
+        payload_len + 5 + crc::len::<C>()
+    }
+
+    fn unpack_cobs(start_byte: u8, mut cobs_byte: u8, data_slice: &mut [u8]) -> bool {
+        if !SerMsg::check_cobs(cobs_byte, data_slice) {
+            return false;
+        }
+        if cobs_byte <= SerMsg::MAX_PACKET_SIZE {
+            while data_slice[cobs_byte as usize] > 0 {
+                let delta: u8 = data_slice[cobs_byte as usize];
+                data_slice[cobs_byte as usize] = start_byte;
+                cobs_byte += delta;
+            }
+            data_slice[cobs_byte as usize] = start_byte;
         }
-        calc_crc
+        true
     }
 
-    fn unpack_cobs(&mut self) -> bool {
-        if self.cobs_byte <= SerMsg::MAX_PACKET_SIZE {
-            if (self.cobs_byte as usize) >= self.payload_len as usize {
+    fn check_cobs(mut cobs_byte: u8, data_slice: &[u8]) -> bool {
+        if cobs_byte <= SerMsg::MAX_PACKET_SIZE {
+            if (cobs_byte as usize) >= data_slice.len() {
                 return false;
             }
-            while self.rcvd_data[self.cobs_byte as usize] > 0 {
-                let delta: u8 = self.rcvd_data[self.cobs_byte as usize];
+            while data_slice[cobs_byte as usize] > 0 {
+                let delta: u8 = data_slice[cobs_byte as usize];
 
                 // check if delta makes us point outside of the payload region
                 // this means the data was corrupted or malformed in a lot of places
                 // and by chance got past the CRC
                 // the saturating add avoids a panic where an overflow might have occured
-                if (delta.saturating_add(self.cobs_byte)) >= self.payload_len {
+                if (delta.saturating_add(cobs_byte)) as usize >= data_slice.len() {
                     return false;
                 }
-                self.rcvd_data[self.cobs_byte as usize] = SerMsg::START_BYTE;
-                self.cobs_byte += delta;
+                cobs_byte += delta;
             }
-            self.rcvd_data[self.cobs_byte as usize] = SerMsg::START_BYTE;
-            true
-        } else {
-            true
         }
+        true
     }
 
-    fn pack_cobs(data_slice: &mut [u8]) -> u8 {
+    fn pack_cobs(start_byte: u8, data_slice: &mut [u8]) -> u8 {
         let mut overhead_byte = 0xFF;
         for (f_ind, val) in (0_u8..).zip(data_slice.iter()) {
-            if *val == SerMsg::START_BYTE {
+            if *val == start_byte {
                 overhead_byte = f_ind;
                 break;
             }
@@ -302,14 +479,14 @@ impl SerMsg {
             let mut r_ind = data_slice.len();
             for _ in 0..data_slice.len() {
                 r_ind -= 1;
-                if data_slice[r_ind] == SerMsg::START_BYTE {
+                if data_slice[r_ind] == start_byte {
                     last_start_byte = r_ind;
                     break;
                 }
             }
             r_ind = data_slice.len() - 1;
             for _ in 0..data_slice.len() {
-                if data_slice[r_ind] == SerMsg::START_BYTE {
+                if data_slice[r_ind] == start_byte {
                     data_slice[r_ind] = (last_start_byte - r_ind) as u8;
                     last_start_byte = r_ind;
                 }
@@ -321,102 +498,833 @@ impl SerMsg {
         }
         overhead_byte
     }
+}
+
+impl<C: Crc> SerMsg<254, C> {
+    /// Returns a new SerMsg instance for parsing messages using the [FramingConfig][FramingConfig]
+    /// and the [Crc][Crc], e.g. a [Crc16][Crc16] or a CRC peripheral of the Microcontroller
+    ///
+    /// [FramingConfig]: FramingConfig
+    /// [Crc]: Crc
+    /// [Crc16]: Crc16
+    pub fn with_crc(config: FramingConfig, crc: C) -> SerMsg<254, C> {
+        SerMsg::with_capacity(config, crc)
+    }
+}
+
+impl<const N: usize, C: Crc> SerMsg<N, C> {
+    /// Same as [SerMsg::with_crc()][with_crc], but for a payload capacity `N` other than 254 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::{Crc16, FramingConfig, SerMsg};
+    ///
+    /// let mut ser_msg = SerMsg::<32, Crc16>::with_capacity(FramingConfig::DEFAULT, Crc16::new());
+    /// ```
+    ///
+    /// [with_crc]: SerMsg::with_crc()
+    pub fn with_capacity(config: FramingConfig, crc: C) -> SerMsg<N, C> {
+        SerMsgBase::with_buffers(config, crc, [0; N], [0; N])
+    }
+}
 
-    fn parse_byte(&mut self, val: u8) -> ParseState {
+impl<'a> SerMsgRef<'a> {
+    /// Returns a new SerMsgRef parsing messages with a payload of up to `buf.len()` bytes into `buf`.
+    ///
+    /// The parser has no buffer for [resynchronization][set_resync], so only the bytes of messages
+    /// failing with a [HighPayloadError][HighPayloadError] are parsed again, see
+    /// [SerMsgRef::with_buffers()][with_buffers].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::{SerMsg, SerMsgRef};
+    ///
+    /// let mut rx_buffer = [0; 16];
+    /// let mut ser_msg = SerMsgRef::new(&mut rx_buffer);
+    ///
+    /// let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+    /// let mut bytes = &send_msg[..];
+    /// let frame = ser_msg.parse(&mut bytes).unwrap().unwrap();
+    /// assert_eq!(frame.payload, &[1, 2, 3]);
+    /// ```
+    ///
+    /// [set_resync]: SerMsgBase::set_resync()
+    /// [HighPayloadError]: ParseState::HighPayloadError
+    /// [with_buffers]: SerMsgRef::with_buffers()
+    pub fn new(buf: &'a mut [u8]) -> SerMsgRef<'a> {
+        SerMsgBase::with_buffers(FramingConfig::DEFAULT, Crc8::new(), buf, &mut [])
+    }
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>, C: Crc> SerMsgBase<B, C> {
+    /// Returns a new parser using the [FramingConfig][FramingConfig] and the [Crc][Crc]
+    /// that parses into `buf` and keeps the bytes of failed messages for the
+    /// [resynchronization][set_resync] in `resync_buf`.
+    ///
+    /// Messages with a payload longer than `buf` are rejected with a
+    /// [HighPayloadError][HighPayloadError]. If `resync_buf` is shorter than `buf`,
+    /// the bytes of a long failed message might not fit and are dropped instead.
+    ///
+    /// [FramingConfig]: FramingConfig
+    /// [Crc]: Crc
+    /// [set_resync]: SerMsgBase::set_resync()
+    /// [HighPayloadError]: ParseState::HighPayloadError
+    pub fn with_buffers(config: FramingConfig, crc: C, buf: B, resync_buf: B) -> SerMsgBase<B, C> {
+        SerMsgBase {
+            config,
+            crc,
+            msg_state: FindByte::Start,
+            rcvd_id: 0,
+            cobs_byte: 0,
+            payload_len: 0,
+            rcvd_data: buf,
+            rcvd_ind: 0,
+            rcvd_crc: 0,
+            crc_ind: 0,
+            rcvd_stop: 0,
+            frame_len: 0,
+            rcvd_bytes: 0,
+            frame_offset: 0,
+            discarded_bytes: 0,
+            next_cobs_ind: None,
+            raw_payload: false,
+            timeout_ms: None,
+            packet_start: 0,
+            resync: false,
+            resync_data: resync_buf,
+            resync_tail: [0; 8],
+            resync_ind: 0,
+            resync_len: 0,
+        }
+    }
+
+    /// Returns the [FramingConfig][FramingConfig] used for parsing
+    ///
+    /// [FramingConfig]: FramingConfig
+    pub fn config(&self) -> FramingConfig {
+        self.config
+    }
+
+    /// Sets the [FramingConfig][FramingConfig] used for parsing. A partially parsed message
+    /// and bytes waiting to be parsed again are dropped.
+    ///
+    /// [FramingConfig]: FramingConfig
+    pub fn set_config(&mut self, config: FramingConfig) {
+        self.config = config;
+        self.msg_state = FindByte::Start;
+        self.resync_ind = 0;
+        self.resync_len = 0;
+    }
+
+    /// Sets the time in milliseconds a message may take from its start byte to its stop byte.
+    /// A message exceeding the timeout is dropped with a [StalePacketError][StalePacketError]
+    /// by [SerMsg.parse_read_bytes_timed()][parse_read_bytes_timed]. `None` disables the timeout,
+    /// which is the default.
+    ///
+    /// [StalePacketError]: ParseState::StalePacketError
+    /// [parse_read_bytes_timed]: SerMsg::parse_read_bytes_timed()
+    pub fn set_timeout(&mut self, timeout_ms: Option<u32>) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    /// Returns the data of the parsed message, should only be used after the [ParseState][ParseState] is [DataReady][DataReady]
+    ///
+    /// [ParseState]: ParseState
+    /// [DataReady]: ParseState::DataReady
+    pub fn return_read_data(&self) -> &[u8] {
+        &self.rcvd_data.as_ref()[..self.rcvd_ind]
+    }
+
+    /// Returns the id of the parsed message, should only be used after the [ParseState][ParseState] is [DataReady][DataReady]
+    ///
+    /// [ParseState]: ParseState
+    /// [DataReady]: ParseState::DataReady
+    pub fn return_msg_id(&self) -> u8 {
+        self.rcvd_id
+    }
+
+    /// Parses the bytes of the input slice. Returns a [ParseState][ParseState] and the amount of bytes parsed
+    /// when an error occured, a complete message was parsed or when all bytes were read.
+    ///
+    /// [ParseState]: ParseState
+    pub fn parse_read_bytes(&mut self, arr: &[u8]) -> (ParseState, usize) {
+        let (result, parsed_bytes) = self.parse_bytes::<TickClock>(arr, None);
+        (ParseState::from_result(result), parsed_bytes)
+    }
+
+    /// Parses the bytes of the slice until a complete message was parsed, an error occurred
+    /// or all bytes were read, and advances the slice past the parsed bytes.
+    /// Returns the message if one is complete and `None` if all bytes were read.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::SerMsg;
+    ///
+    /// let mut rcvd_bytes = SerMsg::create_msg_vec(&[1, 2], 1).unwrap();
+    /// rcvd_bytes.extend(SerMsg::create_msg_vec(&[3], 2).unwrap());
+    ///
+    /// let mut ser_msg = SerMsg::new();
+    /// let mut bytes = &rcvd_bytes[..];
+    /// while !bytes.is_empty() {
+    ///     match ser_msg.parse(&mut bytes) {
+    ///         Ok(Some(frame)) => println!("ID: {}, Payload: {:?}", frame.id, frame.payload),
+    ///         Ok(None) => (),
+    ///         Err(e) => println!("Error: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn parse(&mut self, bytes: &mut &[u8]) -> Result<Option<FrameRef<'_>>, Error> {
+        let (result, parsed_bytes) = self.parse_bytes::<TickClock>(bytes, None);
+        *bytes = &bytes[parsed_bytes..];
+        self.frame_from_result(result)
+    }
+
+    /// Same as [SerMsg.parse()][parse], but additionally checks the
+    /// [timeout][set_timeout] of the current message using the provided [Clock][Clock],
+    /// see [SerMsg.parse_read_bytes_timed()][parse_read_bytes_timed].
+    ///
+    /// [parse]: SerMsg::parse()
+    /// [set_timeout]: SerMsg::set_timeout()
+    /// [Clock]: Clock
+    /// [parse_read_bytes_timed]: SerMsg::parse_read_bytes_timed()
+    pub fn parse_timed<K: Clock>(
+        &mut self,
+        bytes: &mut &[u8],
+        clock: &K,
+    ) -> Result<Option<FrameRef<'_>>, Error> {
+        let (result, parsed_bytes) = self.parse_bytes(bytes, Some(clock));
+        *bytes = &bytes[parsed_bytes..];
+        self.frame_from_result(result)
+    }
+
+    /// Same as [SerMsg.parse_read_bytes()][parse_read_bytes], but additionally checks the
+    /// [timeout][set_timeout] of the current message using the provided [Clock][Clock].
+    ///
+    /// When a byte arrives after the message went stale, the partial message is dropped,
+    /// the byte is parsed as the possible start of a new message and
+    /// [StalePacketError][StalePacketError] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::{ParseState, SerMsg, TickClock};
+    ///
+    /// let send_msg = SerMsg::create_msg_vec(&[1, 2, 3], 1).unwrap();
+    /// let clock = TickClock::new();
+    /// let mut ser_msg = SerMsg::new();
+    /// ser_msg.set_timeout(Some(50));
+    ///
+    /// // The message gets cut off and the rest never arrives
+    /// let (parse_state, _parsed_bytes) = ser_msg.parse_read_bytes_timed(&send_msg[..4], &clock);
+    /// assert!(matches!(parse_state, ParseState::Continue));
+    ///
+    /// clock.advance(50);
+    /// let (parse_state, parsed_bytes) = ser_msg.parse_read_bytes_timed(&send_msg, &clock);
+    /// assert!(matches!(parse_state, ParseState::StalePacketError));
+    ///
+    /// let (parse_state, _parsed_bytes) =
+    ///     ser_msg.parse_read_bytes_timed(&send_msg[parsed_bytes..], &clock);
+    /// assert!(matches!(parse_state, ParseState::DataReady));
+    /// ```
+    ///
+    /// [parse_read_bytes]: SerMsg::parse_read_bytes()
+    /// [set_timeout]: SerMsg::set_timeout()
+    /// [Clock]: Clock
+    /// [StalePacketError]: ParseState::StalePacketError
+    pub fn parse_read_bytes_timed<K: Clock>(
+        &mut self,
+        arr: &[u8],
+        clock: &K,
+    ) -> (ParseState, usize) {
+        let (result, parsed_bytes) = self.parse_bytes(arr, Some(clock));
+        (ParseState::from_result(result), parsed_bytes)
+    }
+
+    /// Enables or disables the resynchronization of the parser, which is disabled by default.
+    ///
+    /// Without resynchronization, all bytes of a message are lost when a
+    /// [CrcError][CrcError], [StopByteError][StopByteError] or [HighPayloadError][HighPayloadError]
+    /// occurs. A start byte caused by line noise can therefore swallow the start byte of a
+    /// real message. With resynchronization enabled the parser keeps these bytes and, after
+    /// reporting the error, parses everything after the false start byte again, so a real message
+    /// starting inside the corrupted one is still recovered.
+    ///
+    /// The kept bytes are parsed at the beginning of the next call to one of the parse functions,
+    /// before the bytes of the input slice. Use [SerMsg.has_resync_bytes()][has_resync_bytes] to
+    /// check if bytes are left after the input was read completely.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::{ParseState, SerMsg};
+    ///
+    /// // A start byte caused by line noise right before the message
+    /// let mut rcvd_bytes = vec![126];
+    /// rcvd_bytes.extend(SerMsg::create_msg_vec(&[1, 2, 3], 1).unwrap());
+    ///
+    /// let mut ser_msg = SerMsg::new();
+    /// ser_msg.set_resync(true);
+    /// let (parse_state, parsed_bytes) = ser_msg.parse_read_bytes(&rcvd_bytes);
+    /// assert!(matches!(parse_state, ParseState::HighPayloadError));
+    ///
+    /// let (parse_state, _parsed_bytes) = ser_msg.parse_read_bytes(&rcvd_bytes[parsed_bytes..]);
+    /// assert!(matches!(parse_state, ParseState::DataReady));
+    /// assert_eq!(ser_msg.return_read_data(), &[1, 2, 3]);
+    /// ```
+    ///
+    /// [CrcError]: ParseState::CrcError
+    /// [StopByteError]: ParseState::StopByteError
+    /// [HighPayloadError]: ParseState::HighPayloadError
+    /// [has_resync_bytes]: SerMsg::has_resync_bytes()
+    pub fn set_resync(&mut self, resync: bool) {
+        self.resync = resync;
+        if !resync {
+            self.resync_ind = 0;
+            self.resync_len = 0;
+        }
+    }
+
+    /// Returns true if bytes of a failed message are waiting to be parsed again,
+    /// see [SerMsg.set_resync()][set_resync]. Calling a parse function with an
+    /// empty slice parses these bytes.
+    ///
+    /// [set_resync]: SerMsg::set_resync()
+    pub fn has_resync_bytes(&self) -> bool {
+        self.resync_ind < self.resync_len
+    }
+
+    /// Returns the field of a message the parser expects next
+    pub fn state(&self) -> FindByte {
+        self.msg_state
+    }
+
+    /// Returns the bytes received of the current message, starting with its start byte.
+    /// The payload is returned as received, with the Consistent Overhead Byte Stuffing applied.
+    ///
+    /// After a message failed, these are the bytes of the failed message until the start byte
+    /// of the next message is parsed, so they can be logged together with the error or checked
+    /// with [inspect_frame()][inspect_frame].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::{ParseState, SerMsg};
+    ///
+    /// let mut send_msg = SerMsg::encode_vec(&[1, 126, 3], 1).unwrap();
+    /// send_msg[4] ^= 0x10;
+    ///
+    /// let mut ser_msg = SerMsg::new();
+    /// let (parse_state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+    /// assert!(matches!(parse_state, ParseState::CrcError));
+    /// if let Some((expected, received)) = ser_msg.frame_crc() {
+    ///     println!(
+    ///         "CRC {:#04x} instead of {:#04x} in {:?} at byte {}",
+    ///         received,
+    ///         expected,
+    ///         ser_msg.frame_bytes().collect::<Vec<_>>(),
+    ///         ser_msg.frame_offset(),
+    ///     );
+    /// }
+    /// assert!(ser_msg.frame_bytes().eq(send_msg[..8].iter().copied()));
+    /// ```
+    ///
+    /// [inspect_frame]: inspect_frame()
+    pub fn frame_bytes(&self) -> FrameBytes<'_, B, C> {
+        FrameBytes {
+            ser_msg: self,
+            ind: 0,
+        }
+    }
+
+    /// Returns the offset of the start byte of the current message, counting all bytes passed
+    /// to the parse functions since the parser was created
+    pub fn frame_offset(&self) -> u64 {
+        self.frame_offset
+    }
+
+    /// Returns the CRC calculated from the received payload and the received CRC of the current
+    /// message as `(expected, received)`, or None if the CRC wasn't received completely.
+    /// Like [SerMsg.frame_bytes()][frame_bytes], they are kept after a [CrcError][CrcError]
+    /// until the next message starts.
+    ///
+    /// [frame_bytes]: SerMsg::frame_bytes()
+    /// [CrcError]: ParseState::CrcError
+    pub fn frame_crc(&self) -> Option<(u32, u32)> {
+        (self.crc_ind == crc::len::<C>()).then(|| (self.crc.finish(), self.rcvd_crc))
+    }
+
+    // Returns the byte of the current message at the index as received
+    fn frame_byte(&self, ind: usize) -> u8 {
+        let payload_len = self.payload_len as usize;
+        match ind {
+            0 => self.config.start_byte(),
+            1 => self.rcvd_id,
+            2 => self.cobs_byte,
+            3 => self.payload_len,
+            ind if ind < 4 + payload_len => self.raw_payload_byte(ind - 4),
+            ind if ind < 4 + payload_len + self.crc_ind => {
+                let crc_ind = ind - 4 - payload_len;
+                (self.rcvd_crc >> (8 * (self.crc_ind - 1 - crc_ind))) as u8
+            }
+            _ => self.rcvd_stop,
+        }
+    }
+
+    // Returns the payload byte at the index as received, packing it again if it was unpacked
+    fn raw_payload_byte(&self, ind: usize) -> u8 {
+        let data = &self.rcvd_data.as_ref()[..self.rcvd_ind];
+        let start_byte = self.config.start_byte();
+        if self.raw_payload || data[ind] != start_byte {
+            return data[ind];
+        }
+        // Every start byte of an unpacked payload is a COBS pointer
+        let next_cobs_ind = data[ind + 1..]
+            .iter()
+            .position(|val| *val == start_byte)
+            .map(|pos| ind + 1 + pos)
+            .or(self.next_cobs_ind);
+        next_cobs_ind.map_or(0, |next_ind| (next_ind - ind) as u8)
+    }
+
+    fn frame_from_result(
+        &self,
+        result: Result<bool, Error>,
+    ) -> Result<Option<FrameRef<'_>>, Error> {
+        match result? {
+            true => Ok(Some(self.read_frame())),
+            false => Ok(None),
+        }
+    }
+
+    fn read_frame(&self) -> FrameRef<'_> {
+        FrameRef {
+            id: self.rcvd_id,
+            payload: self.return_read_data(),
+        }
+    }
+
+    // Returns Ok(true) when a complete message was parsed
+    // Without a clock the type of the clock doesn't matter, the untimed functions use TickClock
+    fn parse_bytes<K: Clock>(
+        &mut self,
+        arr: &[u8],
+        clock: Option<&K>,
+    ) -> (Result<bool, Error>, usize) {
+        match self.parse_resync_bytes(clock.map(|clock| clock.now_ms())) {
+            Ok(false) => (),
+            result => return (result, 0),
+        }
+        // Without new bytes the loop below doesn't check the timeout, so a message cut off
+        // by the sender is reported here
+        if arr.is_empty() && self.drop_stale_frame(clock.map(|clock| clock.now_ms())) {
+            return (Err(Error::Stale), 0);
+        }
+        let mut i = 0;
+        while i < arr.len() {
+            // Without a clock there is no timeout to check after every byte
+            if clock.is_none() && matches!(self.msg_state, FindByte::Payload) {
+                let parsed_bytes = self.parse_payload(&arr[i..]);
+                i += parsed_bytes;
+                self.rcvd_bytes += parsed_bytes as u64;
+                self.frame_len += parsed_bytes;
+                continue;
+            }
+            let val = arr[i];
+            i += 1;
+            let offset = self.rcvd_bytes;
+            self.rcvd_bytes += 1;
+            let now = clock.map(|clock| clock.now_ms());
+            let stale = self.drop_stale_frame(now);
+            let searching_start = self.is_searching_start();
+            let result = self.feed_byte(val, offset, now);
+            if searching_start && self.is_searching_start() {
+                self.discarded_bytes = self.discarded_bytes.wrapping_add(1);
+            }
+            if stale {
+                return (Err(Error::Stale), i);
+            }
+            match result {
+                Ok(false) => (),
+                _ => return (result, i),
+            }
+        }
+        (Ok(false), arr.len())
+    }
+
+    // Drops the message in progress and returns true if it exceeded the timeout
+    fn drop_stale_frame(&mut self, now: Option<u32>) -> bool {
+        if let (Some(now), Some(timeout_ms), false) =
+            (now, self.timeout_ms, self.is_searching_start())
+        {
+            if now.wrapping_sub(self.packet_start) >= timeout_ms {
+                self.msg_state = FindByte::Start;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn parse_resync_bytes(&mut self, now: Option<u32>) -> Result<bool, Error> {
+        while self.resync_ind < self.resync_len {
+            let val = self.resync_byte(self.resync_ind);
+            // The resync bytes are the last input bytes parsed
+            let offset = self.rcvd_bytes - (self.resync_len - self.resync_ind) as u64;
+            self.resync_ind += 1;
+            if self.feed_byte(val, offset, now)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn feed_byte(&mut self, val: u8, offset: u64, now: Option<u32>) -> Result<bool, Error> {
+        let was_searching_start = self.is_searching_start();
+        let result = self.parse_byte(val);
+        if !was_searching_start {
+            self.frame_len += 1;
+        } else if !self.is_searching_start() {
+            self.frame_len = 1;
+            self.frame_offset = offset;
+            if let Some(now) = now {
+                self.packet_start = now;
+            }
+        }
+        if let (true, Err(e)) = (self.resync, result) {
+            match e {
+                Error::Crc { .. } | Error::StopByte { .. } | Error::HighPayload { .. } => {
+                    self.keep_resync_bytes(val, e)
+                }
+                _ => (),
+            }
+        }
+        result
+    }
+
+    // Puts all bytes after the start byte of the failed message in front of
+    // the bytes that still wait to be parsed again. The failed message either started inside
+    // the waiting bytes or all waiting bytes were parsed, so everything fits into the buffer.
+    fn keep_resync_bytes(&mut self, val: u8, e: Error) {
+        let failed_len = match e {
+            Error::HighPayload { .. } => 3,
+            Error::Crc { .. } => 3 + self.rcvd_ind + crc::len::<C>(),
+            _ => 4 + self.rcvd_ind + crc::len::<C>(),
+        };
+        let waiting_len = self.resync_len - self.resync_ind;
+        if failed_len + waiting_len > self.resync_data.as_ref().len() + self.resync_tail.len() {
+            // Only happens if the resync buffer is shorter than the payload buffer
+            return;
+        }
+        if failed_len > self.resync_ind {
+            for ind in (0..waiting_len).rev() {
+                self.set_resync_byte(failed_len + ind, self.resync_byte(self.resync_ind + ind));
+            }
+        } else {
+            for ind in 0..waiting_len {
+                self.set_resync_byte(failed_len + ind, self.resync_byte(self.resync_ind + ind));
+            }
+        }
+        self.resync_ind = 0;
+        self.resync_len = failed_len + waiting_len;
+
+        self.set_resync_byte(0, self.rcvd_id);
+        self.set_resync_byte(1, self.cobs_byte);
+        if let Error::HighPayload { .. } = e {
+            self.set_resync_byte(2, val);
+            return;
+        }
+        self.set_resync_byte(2, self.payload_len);
+        self.restore_raw_payload();
+        for ind in 0..self.rcvd_ind {
+            self.set_resync_byte(3 + ind, self.rcvd_data.as_ref()[ind]);
+        }
+        // The received CRC already contains the last byte of a failed CRC check
+        let crc_bytes = self.rcvd_crc.to_be_bytes();
+        for ind in 0..crc::len::<C>() {
+            self.set_resync_byte(
+                3 + self.rcvd_ind + ind,
+                crc_bytes[4 - crc::len::<C>() + ind],
+            );
+        }
+        if let Error::StopByte { .. } = e {
+            self.set_resync_byte(failed_len - 1, val);
+        }
+    }
+
+    // Stores the payload bytes at the start of the slice, updates the CRC and unpacks the
+    // overhead byte stuffing. Returns the amount of parsed bytes.
+    fn parse_payload(&mut self, arr: &[u8]) -> usize {
+        let start_ind = self.rcvd_ind;
+        let end_ind = (start_ind + arr.len()).min(self.payload_len as usize);
+        let bytes = &arr[..end_ind - start_ind];
+        self.crc.update(bytes);
+        self.rcvd_data.as_mut()[start_ind..end_ind].copy_from_slice(bytes);
+
+        let start_byte = self.config.start_byte();
+        let find_start_byte = |data: &[u8], ind: usize| {
+            data[ind..end_ind]
+                .iter()
+                .position(|val| *val == start_byte)
+                .map(|pos| ind + pos)
+        };
+        let mut raw_ind = find_start_byte(self.rcvd_data.as_ref(), start_ind);
+        while !self.raw_payload {
+            let cobs_ind = self.next_cobs_ind.filter(|cobs_ind| *cobs_ind < end_ind);
+            match (cobs_ind, raw_ind) {
+                (Some(cobs_ind), _) if raw_ind.unwrap_or(end_ind) >= cobs_ind => {
+                    let delta = self.rcvd_data.as_ref()[cobs_ind];
+                    self.rcvd_data.as_mut()[cobs_ind] = start_byte;
+                    self.next_cobs_ind = match delta {
+                        0 => None,
+                        _ => Some(cobs_ind + delta as usize),
+                    };
+                    if raw_ind == Some(cobs_ind) {
+                        raw_ind = find_start_byte(self.rcvd_data.as_ref(), cobs_ind + 1);
+                    }
+                }
+                (_, Some(raw_ind)) => {
+                    // A start byte that wasn't packed, the bytes from here on are stored as received
+                    self.rcvd_ind = raw_ind;
+                    self.restore_raw_payload();
+                }
+                (_, None) => break,
+            }
+        }
+        self.rcvd_ind = end_ind;
+        if self.rcvd_ind == self.payload_len as usize {
+            self.msg_state = FindByte::Crc;
+        }
+        end_ind - start_ind
+    }
+
+    // Packs the already unpacked payload bytes again, so the payload is stored as received
+    fn restore_raw_payload(&mut self) {
+        if self.raw_payload {
+            return;
+        }
+        let start_byte = self.config.start_byte();
+        let mut next_cobs_ind = self.next_cobs_ind;
+        for (ind, val) in self.rcvd_data.as_mut()[..self.rcvd_ind]
+            .iter_mut()
+            .enumerate()
+            .rev()
+        {
+            if *val == start_byte {
+                *val = next_cobs_ind.map_or(0, |next_ind| (next_ind - ind) as u8);
+                next_cobs_ind = Some(ind);
+            }
+        }
+        self.raw_payload = true;
+    }
+
+    fn resync_byte(&self, ind: usize) -> u8 {
+        let resync_data = self.resync_data.as_ref();
+        match resync_data.get(ind) {
+            Some(val) => *val,
+            None => self.resync_tail[ind - resync_data.len()],
+        }
+    }
+
+    fn set_resync_byte(&mut self, ind: usize, val: u8) {
+        let resync_data = self.resync_data.as_mut();
+        match resync_data.get_mut(ind) {
+            Some(dest) => *dest = val,
+            None => self.resync_tail[ind - resync_data.len()] = val,
+        }
+    }
+
+    fn is_searching_start(&self) -> bool {
+        matches!(self.msg_state, FindByte::Start)
+    }
+
+    // Returns Ok(true) when a complete message was parsed
+    fn parse_byte(&mut self, val: u8) -> Result<bool, Error> {
         match self.msg_state {
             FindByte::Start => {
-                if val == SerMsg::START_BYTE {
-                    self.rcvd_data.fill(0);
+                if val == self.config.start_byte() {
+                    self.crc.reset();
                     self.rcvd_ind = 0;
+                    self.rcvd_crc = 0;
+                    self.crc_ind = 0;
+                    self.raw_payload = false;
                     self.msg_state = FindByte::Id;
                 }
-                ParseState::Continue
+                Ok(false)
             }
 
             FindByte::Id => {
                 self.rcvd_id = val;
                 self.msg_state = FindByte::Overhead;
-                ParseState::Continue
+                Ok(false)
             }
 
             FindByte::Overhead => {
                 self.cobs_byte = val;
+                self.next_cobs_ind = if val <= SerMsg::MAX_PACKET_SIZE {
+                    Some(val as usize)
+                } else {
+                    None
+                };
                 self.msg_state = FindByte::PayloadLen;
-                ParseState::Continue
+                Ok(false)
             }
 
             FindByte::PayloadLen => {
                 self.payload_len = val;
-                if val > SerMsg::MAX_PACKET_SIZE {
+                if val > self.config.max_payload() || val as usize > self.rcvd_data.as_ref().len() {
                     self.msg_state = FindByte::Start;
-                    ParseState::HighPayloadError
+                    Err(Error::HighPayload { len: val })
                 } else if val == 0 {
                     self.msg_state = FindByte::Crc;
-                    ParseState::Continue
+                    Ok(false)
                 } else {
                     self.msg_state = FindByte::Payload;
-                    ParseState::Continue
+                    Ok(false)
                 }
             }
 
             FindByte::Payload => {
-                self.rcvd_data[self.rcvd_ind] = val;
-                self.rcvd_ind += 1;
-                if (self.payload_len as usize - self.rcvd_ind) == 0 {
-                    self.msg_state = FindByte::Crc;
-                }
-                ParseState::Continue
+                self.parse_payload(core::slice::from_ref(&val));
+                Ok(false)
             }
 
             FindByte::Crc => {
-                if val == SerMsg::retrieve_crc(&self.rcvd_data[..self.rcvd_ind]) {
+                self.rcvd_crc = (self.rcvd_crc << 8) | val as u32;
+                self.crc_ind += 1;
+                if self.crc_ind < crc::len::<C>() {
+                    return Ok(false);
+                }
+                let calc_crc = self.crc.finish();
+                if self.rcvd_crc == calc_crc {
                     self.msg_state = FindByte::End;
-                    ParseState::Continue
+                    Ok(false)
                 } else {
                     self.msg_state = FindByte::Start;
-                    ParseState::CrcError
+                    Err(Error::Crc {
+                        expected: calc_crc,
+                        received: self.rcvd_crc,
+                    })
                 }
             }
 
             FindByte::End => {
                 self.msg_state = FindByte::Start;
-                if val == SerMsg::STOP_BYTE {
-                    if self.unpack_cobs() {
-                        ParseState::DataReady
+                self.rcvd_stop = val;
+                if val == self.config.stop_byte() {
+                    let unpacked = if self.raw_payload {
+                        // The payload is only changed if it can be unpacked completely
+                        let unpacked = SerMsg::unpack_cobs(
+                            self.config.start_byte(),
+                            self.cobs_byte,
+                            &mut self.rcvd_data.as_mut()[..self.rcvd_ind],
+                        );
+                        if unpacked {
+                            self.raw_payload = false;
+                            self.next_cobs_ind = None;
+                        }
+                        unpacked
                     } else {
-                        ParseState::COBSError
+                        // The overhead byte stuffing must end inside the payload
+                        self.next_cobs_ind.is_none()
+                    };
+                    if unpacked {
+                        Ok(true)
+                    } else {
+                        Err(Error::Cobs)
                     }
                 } else {
-                    ParseState::StopByteError
+                    Err(Error::StopByte { found: val })
                 }
             }
         }
     }
 }
 
+/// Iterator over the bytes of the current message as received, returned by
+/// [SerMsg.frame_bytes()][frame_bytes]
+///
+/// [frame_bytes]: SerMsg::frame_bytes()
+pub struct FrameBytes<'a, B, C> {
+    ser_msg: &'a SerMsgBase<B, C>,
+    ind: usize,
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>, C: Crc> Iterator for FrameBytes<'_, B, C> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.ind == self.ser_msg.frame_len {
+            return None;
+        }
+        let val = self.ser_msg.frame_byte(self.ind);
+        self.ind += 1;
+        Some(val)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.ser_msg.frame_len - self.ind;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>, C: Crc> ExactSizeIterator for FrameBytes<'_, B, C> {}
+
 cfg_if::cfg_if! {
     if #[cfg(feature="alloc")] {
         extern crate alloc;
         use alloc::{vec::Vec};
         impl SerMsg {
             /// Packs the slice into the message format and returns a Vector containing the message bytes.
-            /// Returns None if the input slice exceeds the maximum supported length of 254 bytes.
-            #[allow(clippy::vec_init_then_push)]
+            /// Returns None if the input slice exceeds the maximum supported length of 254 bytes,
+            /// see [SerMsg::encode_vec()][encode_vec] for the reason of the failure.
+            ///
+            /// [encode_vec]: SerMsg::encode_vec()
             pub fn create_msg_vec(data: &[u8], id: u8) -> Option<Vec<u8>> {
-                if data.len() > SerMsg::MAX_PACKET_SIZE as usize {
-                    return None;
-                }
-                let mut data_vec: Vec<u8> = Vec::with_capacity(data.len() + 6);
-                data_vec.push(SerMsg::START_BYTE);
-                data_vec.push(id);
-                data_vec.push(0); // 0 as a placeholder for the COBS
-                data_vec.push(data.len() as u8);
-                data_vec.extend(data);
-                data_vec[2] = SerMsg::pack_cobs(&mut data_vec[4..4 + data.len()]);
-                let crc = SerMsg::retrieve_crc(&data_vec[4..4 + data.len()]);
-                data_vec.push(crc);
-                data_vec.push(SerMsg::STOP_BYTE);
-                Some(data_vec)
+                SerMsg::encode_vec(data, id).ok()
+            }
+
+            /// Packs the slice into the message format and returns a Vector containing the message bytes.
+            /// Returns [PayloadTooLarge][PayloadTooLarge] if the input slice exceeds the maximum
+            /// supported length of 254 bytes.
+            ///
+            /// [PayloadTooLarge]: Error::PayloadTooLarge
+            pub fn encode_vec(data: &[u8], id: u8) -> Result<Vec<u8>, Error> {
+                let mut data_vec: Vec<u8> = Vec::new();
+                SerMsg::encode_extend(data, id, &mut data_vec)?;
+                Ok(data_vec)
+            }
+
+            /// Packs the slice into the message format and appends the message to the Vector.
+            /// Returns the length of the message. The Vector only reallocates if its capacity
+            /// is too small to hold the message.
+            /// Returns [PayloadTooLarge][PayloadTooLarge] if the input slice exceeds the maximum
+            /// supported length of 254 bytes.
+            ///
+            /// [PayloadTooLarge]: Error::PayloadTooLarge
+            pub fn encode_extend(data: &[u8], id: u8, data_vec: &mut Vec<u8>) -> Result<usize, Error> {
+                SerMsg::encode_slices_extend(&[data], id, data_vec)
+            }
+
+            /// Same as [SerMsg::encode_vec()][encode_vec], but the payload is the concatenation
+            /// of all slices in `parts`, see [SerMsg::encode_slices_into()][encode_slices_into].
+            ///
+            /// [encode_vec]: SerMsg::encode_vec()
+            /// [encode_slices_into]: SerMsg::encode_slices_into()
+            pub fn encode_slices_vec(parts: &[&[u8]], id: u8) -> Result<Vec<u8>, Error> {
+                let mut data_vec: Vec<u8> = Vec::new();
+                SerMsg::encode_slices_extend(parts, id, &mut data_vec)?;
+                Ok(data_vec)
+            }
+
+            /// Same as [SerMsg::encode_extend()][encode_extend], but the payload is the concatenation
+            /// of all slices in `parts`, see [SerMsg::encode_slices_into()][encode_slices_into].
+            ///
+            /// [encode_extend]: SerMsg::encode_extend()
+            /// [encode_slices_into]: SerMsg::encode_slices_into()
+            pub fn encode_slices_extend(parts: &[&[u8]], id: u8, data_vec: &mut Vec<u8>) -> Result<usize, Error> {
+                FramingConfig::DEFAULT.encode_slices_extend(parts, id, data_vec)
             }
         }
     }
@@ -0,0 +1,276 @@
+//! Sharing one port between several parts of an application with a background reader thread.
+
+use std::io::{self, Read, Write};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use std::vec::Vec;
+
+use crate::{FramingConfig, LinkStats, Message, SerMsg};
+
+// The time the reader thread waits before reading again if a read would block
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+struct Subscriptions {
+    // Set when the reader thread stopped, new subscribers are disconnected right away
+    closed: bool,
+    senders: Vec<(RangeInclusive<u8>, Sender<Message>)>,
+}
+
+/// Owns the reading half of a port and a parser running in a background thread, which hands
+/// the received messages to the subscribers of their packet ids.
+///
+/// Messages are sent through [LinkSenders][LinkSender], which can be cloned and used from
+/// several threads at once. Each message is written completely before the next one starts.
+///
+/// The reader thread stops when the port is closed, reading from it fails or the link is
+/// [shut down][shutdown]. All subscribers are disconnected then, so their receivers return
+/// an error once the remaining messages were received.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::LinkHandle;
+/// use std::net::{TcpListener, TcpStream};
+/// use std::time::Duration;
+///
+/// // Stands in for a serial port, which is split into its halves with try_clone() as well
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let port = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+/// let (device, _) = listener.accept().unwrap();
+/// // The reader thread checks for a shutdown whenever a read returns
+/// port.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+///
+/// let link = LinkHandle::spawn(port.try_clone().unwrap(), port).unwrap();
+/// let status_msgs = link.subscribe(1);
+///
+/// // The device echoes the message back
+/// let sender = link.sender();
+/// sender.send(&[1, 2, 3], 1).unwrap();
+/// std::io::copy(&mut std::io::Read::take(&device, 9), &mut &device).unwrap();
+///
+/// let msg = status_msgs.recv().unwrap();
+/// assert_eq!(msg.payload, [1, 2, 3]);
+/// link.shutdown().unwrap();
+/// ```
+///
+/// [LinkSender]: LinkSender
+/// [shutdown]: LinkHandle::shutdown()
+pub struct LinkHandle {
+    sender: LinkSender,
+    subscriptions: Arc<Mutex<Subscriptions>>,
+    stop: Arc<AtomicBool>,
+    reader: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl LinkHandle {
+    /// Spawns the reader thread parsing the bytes of `reader` with a [SerMsg::new()][new],
+    /// the messages are sent through `writer`.
+    ///
+    /// The reader thread can only notice a shutdown when a read returns, so `reader` should
+    /// have a read timeout. Reads that time out or would block are retried, a read returning
+    /// 0 bytes is treated as the port being closed.
+    ///
+    /// [new]: SerMsg::new()
+    pub fn spawn<R, W>(reader: R, writer: W) -> io::Result<LinkHandle>
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        LinkHandle::with_parser(SerMsg::new(), reader, writer)
+    }
+
+    /// Same as [LinkHandle::spawn()][spawn], but the bytes are parsed by `ser_msg`, e.g. to enable
+    /// the resync. The messages are sent with the [FramingConfig][FramingConfig] of the parser.
+    ///
+    /// [spawn]: LinkHandle::spawn()
+    /// [FramingConfig]: FramingConfig
+    pub fn with_parser<R, W>(ser_msg: SerMsg, reader: R, writer: W) -> io::Result<LinkHandle>
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        let config = ser_msg.config();
+        let stats = Arc::new(Mutex::new(LinkStats::new()));
+        let subscriptions = Arc::new(Mutex::new(Subscriptions {
+            closed: false,
+            senders: Vec::new(),
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let link_reader = LinkReader {
+            reader,
+            ser_msg,
+            subscriptions: subscriptions.clone(),
+            stop: stop.clone(),
+            stats: stats.clone(),
+        };
+        let reader = thread::Builder::new()
+            .name("serialmessage-reader".into())
+            .spawn(move || link_reader.run())?;
+        Ok(LinkHandle {
+            sender: LinkSender {
+                config,
+                writer: Arc::new(Mutex::new(writer)),
+                stats,
+            },
+            subscriptions,
+            stop,
+            reader: Some(reader),
+        })
+    }
+
+    /// Returns a [LinkSender][LinkSender] sending messages through the shared writer
+    ///
+    /// [LinkSender]: LinkSender
+    pub fn sender(&self) -> LinkSender {
+        self.sender.clone()
+    }
+
+    /// Same as [LinkSender.send()][send]
+    ///
+    /// [send]: LinkSender::send()
+    pub fn send(&self, data: &[u8], id: u8) -> io::Result<()> {
+        self.sender.send(data, id)
+    }
+
+    /// Returns a receiver of all messages with the packet id received from now on
+    pub fn subscribe(&self, id: u8) -> Receiver<Message> {
+        self.subscribe_range(id..=id)
+    }
+
+    /// Returns a receiver of all messages with a packet id inside the range received from now on.
+    /// A message matching several subscriptions is sent to each of them.
+    pub fn subscribe_range(&self, ids: RangeInclusive<u8>) -> Receiver<Message> {
+        let (sender, receiver) = mpsc::channel();
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        if !subscriptions.closed {
+            subscriptions.senders.push((ids, sender));
+        }
+        receiver
+    }
+
+    /// Returns a snapshot of the [LinkStats][LinkStats] of the received and sent messages
+    ///
+    /// [LinkStats]: LinkStats
+    pub fn stats(&self) -> LinkStats {
+        self.sender.stats.lock().unwrap().clone()
+    }
+
+    /// Returns true until the reader thread stopped
+    pub fn is_running(&self) -> bool {
+        self.reader
+            .as_ref()
+            .is_some_and(|reader| !reader.is_finished())
+    }
+
+    /// Stops the reader thread and waits for it. Returns the error that stopped the reader
+    /// thread before, e.g. an [UnexpectedEof][UnexpectedEof] if the port was closed.
+    ///
+    /// [UnexpectedEof]: io::ErrorKind::UnexpectedEof
+    pub fn shutdown(mut self) -> io::Result<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        match self.reader.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(io::Error::other("the reader thread panicked")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Stops the reader thread without waiting for it
+impl Drop for LinkHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Sends messages through the writer shared by all clones, returned by
+/// [LinkHandle.sender()][sender]
+///
+/// [sender]: LinkHandle::sender()
+#[derive(Clone)]
+pub struct LinkSender {
+    config: FramingConfig,
+    writer: Arc<Mutex<dyn Write + Send>>,
+    stats: Arc<Mutex<LinkStats>>,
+}
+
+impl LinkSender {
+    /// Packs the slice into the message format and writes the message.
+    /// Returns an [io::Error] of the kind [InvalidInput][InvalidInput] wrapping the
+    /// [Error][Error] if the message can't be created.
+    ///
+    /// [InvalidInput]: io::ErrorKind::InvalidInput
+    /// [Error]: crate::Error
+    pub fn send(&self, data: &[u8], id: u8) -> io::Result<()> {
+        let msg = self
+            .config
+            .encode_vec(data, id)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        {
+            let mut writer = self.writer.lock().unwrap();
+            writer.write_all(&msg)?;
+            writer.flush()?;
+        }
+        self.stats.lock().unwrap().record_sent(&msg);
+        Ok(())
+    }
+}
+
+struct LinkReader<R> {
+    reader: R,
+    ser_msg: SerMsg,
+    subscriptions: Arc<Mutex<Subscriptions>>,
+    stop: Arc<AtomicBool>,
+    stats: Arc<Mutex<LinkStats>>,
+}
+
+impl<R: Read> LinkReader<R> {
+    fn run(mut self) -> io::Result<()> {
+        let result = self.read_messages();
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        subscriptions.closed = true;
+        // Disconnects all subscribers
+        subscriptions.senders.clear();
+        result
+    }
+
+    fn read_messages(&mut self) -> io::Result<()> {
+        let mut buf = [0; 256];
+        while !self.stop.load(Ordering::Relaxed) {
+            let read_bytes = match self.reader.read(&mut buf) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "the port was closed",
+                    ))
+                }
+                Ok(read_bytes) => read_bytes,
+                Err(e) => match e.kind() {
+                    io::ErrorKind::WouldBlock => {
+                        thread::sleep(POLL_INTERVAL);
+                        continue;
+                    }
+                    io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                    _ => return Err(e),
+                },
+            };
+            let mut bytes = &buf[..read_bytes];
+            let mut stats = self.stats.lock().unwrap();
+            while !bytes.is_empty() || self.ser_msg.has_resync_bytes() {
+                if let Ok(Some(frame)) = self.ser_msg.parse_stats(&mut bytes, &mut stats) {
+                    let msg = Message::from_parsed(frame);
+                    let mut subscriptions = self.subscriptions.lock().unwrap();
+                    // Subscribers that dropped their receiver are removed
+                    subscriptions.senders.retain(|(ids, sender)| {
+                        !ids.contains(&msg.id) || sender.send(msg.clone()).is_ok()
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+//! An owned message that can be stored, compared and sent to other threads.
+
+use core::convert::TryFrom;
+
+use crate::{decode_frame, Error, FrameRef, SerMsg};
+
+/// An owned message, e.g. returned by the [Iterator] implementation of [Frames][Frames].
+///
+/// The payload is a `heapless::Vec<u8, 254>`, so messages don't allocate and have the same
+/// type with and without the `alloc` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashSet;
+/// use std::convert::TryFrom;
+/// use serialmessage::Message;
+///
+/// let msg = Message::new(1, &[1, 2, 3]).unwrap();
+/// let send_msg = msg.encode();
+/// assert_eq!(Message::try_from(&send_msg[..]), Ok(msg.clone()));
+///
+/// let mut seen_msgs = HashSet::new();
+/// assert!(seen_msgs.insert(msg.clone()));
+/// assert!(!seen_msgs.insert(msg));
+/// ```
+///
+/// [Frames]: crate::Frames
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Message {
+    /// The packet id of the message
+    pub id: u8,
+    /// The unpacked payload of the message
+    pub payload: heapless::Vec<u8, 254>,
+}
+
+impl Message {
+    /// Returns a message with a copy of the payload.
+    /// Returns [PayloadTooLarge][PayloadTooLarge] if the payload exceeds the maximum
+    /// supported length of 254 bytes.
+    ///
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    pub fn new(id: u8, payload: &[u8]) -> Result<Message, Error> {
+        let len = payload.len();
+        Ok(Message {
+            id,
+            payload: heapless::Vec::from_slice(payload)
+                .map_err(|_| Error::PayloadTooLarge { len })?,
+        })
+    }
+
+    // Copies a message returned by a parser, whose payload never exceeds 254 bytes
+    pub(crate) fn from_parsed(frame: FrameRef<'_>) -> Message {
+        Message {
+            id: frame.id,
+            payload: frame.payload.iter().copied().collect(),
+        }
+    }
+
+    /// Returns the message as a [FrameRef][FrameRef]
+    ///
+    /// [FrameRef]: FrameRef
+    pub fn as_frame_ref(&self) -> FrameRef<'_> {
+        FrameRef {
+            id: self.id,
+            payload: &self.payload,
+        }
+    }
+
+    /// Same as [SerMsg::encode_into()][encode_into] with the payload and id of the message
+    ///
+    /// [encode_into]: SerMsg::encode_into()
+    pub fn encode_into(&self, out: &mut [u8]) -> Result<usize, Error> {
+        SerMsg::encode_into(&self.payload, self.id, out)
+    }
+}
+
+/// Copies the payload of the message, see [Message::new()][new]
+///
+/// [new]: Message::new()
+impl TryFrom<FrameRef<'_>> for Message {
+    type Error = Error;
+
+    fn try_from(frame: FrameRef<'_>) -> Result<Self, Self::Error> {
+        Message::new(frame.id, frame.payload)
+    }
+}
+
+/// Decodes a complete message, see [decode_frame()][decode_frame]
+///
+/// [decode_frame]: decode_frame()
+impl TryFrom<&[u8]> for Message {
+    type Error = Error;
+
+    fn try_from(frame: &[u8]) -> Result<Self, Self::Error> {
+        let (id, payload) = decode_frame(frame)?;
+        Ok(Message {
+            id,
+            payload: payload.iter().collect(),
+        })
+    }
+}
+
+impl Message {
+    /// Packs the payload and id of the message into the message format
+    pub fn encode(&self) -> heapless::Vec<u8, 260> {
+        let mut msg = heapless::Vec::new();
+        // The payload of the message never exceeds 254 bytes, so the message always fits
+        msg.resize(SerMsg::encoded_len(self.payload.len()), 0)
+            .unwrap();
+        self.encode_into(&mut msg).unwrap();
+        msg
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="alloc")] {
+        use alloc::vec::Vec;
+
+        impl Message {
+            /// Same as [SerMsg::encode_vec()][encode_vec] with the payload and id of the message
+            ///
+            /// [encode_vec]: SerMsg::encode_vec()
+            pub fn encode_vec(&self) -> Vec<u8> {
+                // The payload of the message never exceeds 254 bytes, so encoding can't fail
+                SerMsg::encode_vec(&self.payload, self.id).unwrap()
+            }
+        }
+    }
+}
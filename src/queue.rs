@@ -0,0 +1,432 @@
+//! Keeping several received messages until the application retrieves them.
+
+use crate::{Crc, Crc8, Error, FrameRef, SerMsg, SerMsgBase};
+
+/// What happens to a completed message when the queue of a [QueuedSerMsg][QueuedSerMsg] is full.
+///
+/// [QueuedSerMsg]: QueuedSerMsg
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// The oldest message in the queue is dropped to make room for the new one
+    DropOldest,
+    /// The new message is dropped
+    DropNewest,
+    /// No more bytes are parsed and [QueueFull][QueueFull] is returned until a message is popped
+    ///
+    /// [QueueFull]: Error::QueueFull
+    Error,
+}
+
+/// The counters of a [QueuedSerMsg][QueuedSerMsg].
+///
+/// [QueuedSerMsg]: QueuedSerMsg
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueCounters {
+    /// The amount of completed messages
+    pub received: u32,
+    /// The amount of completed messages dropped because the queue was full
+    /// or their payload exceeded the payload capacity of the queue
+    pub dropped: u32,
+    /// The amount of messages that failed to parse
+    pub errors: u32,
+}
+
+/// The storage of the messages queued by a [QueuedSerMsg][QueuedSerMsg].
+///
+/// Implemented by [FrameRing][FrameRing] without allocations and by [FrameDeque][FrameDeque]
+/// with the `alloc` feature.
+///
+/// [QueuedSerMsg]: QueuedSerMsg
+/// [FrameRing]: FrameRing
+/// [FrameDeque]: crate::FrameDeque
+pub trait FrameStore {
+    /// The owned message returned when popping a message
+    type Frame;
+
+    /// Returns the maximum amount of messages
+    fn capacity(&self) -> usize;
+
+    /// Returns the amount of stored messages
+    fn len(&self) -> usize;
+
+    /// Returns the maximum payload length of a stored message, longer messages are dropped
+    fn max_payload(&self) -> usize {
+        SerMsg::MAX_PACKET_SIZE as usize
+    }
+
+    /// Returns true if no message is stored
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends a message, is only called if the storage isn't full
+    fn push_back(&mut self, frame: FrameRef<'_>);
+
+    /// Removes and returns the oldest message
+    fn pop_front(&mut self) -> Option<Self::Frame>;
+
+    /// Returns the oldest message without removing it
+    fn front(&self) -> Option<FrameRef<'_>>;
+}
+
+/// A message with a payload of up to `P` bytes stored in a [FrameRing][FrameRing].
+///
+/// [FrameRing]: FrameRing
+#[derive(Debug, Clone)]
+pub struct QueuedFrame<const P: usize = 254> {
+    id: u8,
+    len: u8,
+    data: [u8; P],
+}
+
+impl<const P: usize> QueuedFrame<P> {
+    pub(crate) const EMPTY: QueuedFrame<P> = QueuedFrame {
+        id: 0,
+        len: 0,
+        data: [0; P],
+    };
+
+    pub(crate) fn set(&mut self, frame: FrameRef<'_>) {
+        self.id = frame.id;
+        self.len = frame.payload.len() as u8;
+        self.data[..frame.payload.len()].copy_from_slice(frame.payload);
+    }
+
+    /// Returns the packet id of the message
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Returns the unpacked payload of the message
+    pub fn payload(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+
+    /// Returns the message as a [FrameRef][FrameRef]
+    ///
+    /// [FrameRef]: FrameRef
+    pub fn as_frame_ref(&self) -> FrameRef<'_> {
+        FrameRef {
+            id: self.id,
+            payload: self.payload(),
+        }
+    }
+}
+
+/// A ring buffer of `N` messages with a payload of up to `P` bytes, for use without allocations.
+///
+/// The payload capacity should match the one of the parser, e.g. a `FrameRing<8, 16>` for a
+/// [SerMsg<16>][SerMsg], so the queue doesn't reserve 254 bytes for every message.
+/// Messages with a longer payload are dropped. A FrameRing without room for a message fails
+/// to compile:
+///
+/// ```rust,compile_fail
+/// let ring = serialmessage::FrameRing::<0>::new();
+/// ```
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{FrameRing, Overflow, QueuedSerMsg, SerMsg};
+///
+/// let ser_msg = SerMsg::<16>::default();
+/// let mut queued_msg = QueuedSerMsg::with_parser(ser_msg, FrameRing::<8, 16>::new(), Overflow::Error);
+/// let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+/// queued_msg.parse(&mut &send_msg[..]).unwrap();
+/// assert_eq!(queued_msg.pop().unwrap().payload(), &[1, 2, 3]);
+/// ```
+///
+/// [SerMsg]: SerMsg
+#[derive(Debug, Clone)]
+pub struct FrameRing<const N: usize, const P: usize = 254> {
+    frames: [QueuedFrame<P>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize, const P: usize> Default for FrameRing<N, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const P: usize> FrameRing<N, P> {
+    // Evaluated by new(), fails the build for a ring without room for a message
+    const NOT_EMPTY: () = assert!(N > 0, "the ring needs room for at least one message");
+
+    /// Returns an empty FrameRing
+    pub const fn new() -> FrameRing<N, P> {
+        let () = Self::NOT_EMPTY;
+        FrameRing {
+            frames: [QueuedFrame::EMPTY; N],
+            head: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<const N: usize, const P: usize> FrameStore for FrameRing<N, P> {
+    type Frame = QueuedFrame<P>;
+
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn max_payload(&self) -> usize {
+        P
+    }
+
+    fn push_back(&mut self, frame: FrameRef<'_>) {
+        self.frames[(self.head + self.len) % N].set(frame);
+        self.len += 1;
+    }
+
+    fn pop_front(&mut self) -> Option<QueuedFrame<P>> {
+        if self.is_empty() {
+            return None;
+        }
+        let frame = self.frames[self.head].clone();
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(frame)
+    }
+
+    fn front(&self) -> Option<FrameRef<'_>> {
+        match self.is_empty() {
+            true => None,
+            false => Some(self.frames[self.head].as_frame_ref()),
+        }
+    }
+}
+
+/// A parser keeping completed messages in a queue until they are popped.
+///
+/// A [SerMsg][SerMsg] only holds the last parsed message, which is overwritten as soon as the
+/// next message starts. The QueuedSerMsg parses all bytes it is given and stores the completed
+/// messages in a [FrameStore][FrameStore], so several messages received with one read don't have
+/// to be handled right away. Messages that fail to parse are counted and skipped.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{FrameRing, Overflow, QueuedSerMsg, SerMsg};
+///
+/// let mut rcvd_bytes = SerMsg::encode_vec(&[1, 2], 1).unwrap();
+/// rcvd_bytes.extend(SerMsg::encode_vec(&[3], 2).unwrap());
+/// rcvd_bytes.extend(SerMsg::encode_vec(&[4], 3).unwrap());
+///
+/// let mut queued_msg = QueuedSerMsg::new(FrameRing::<2>::new(), Overflow::DropOldest);
+/// let mut bytes = &rcvd_bytes[..];
+/// queued_msg.parse(&mut bytes).unwrap();
+/// assert_eq!(queued_msg.len(), 2);
+/// assert_eq!(queued_msg.counters().dropped, 1);
+///
+/// let frame = queued_msg.pop().unwrap();
+/// assert_eq!((frame.id(), frame.payload()), (2, &[3][..]));
+/// ```
+///
+/// [SerMsg]: SerMsg
+/// [FrameStore]: FrameStore
+pub struct QueuedSerMsg<S, B = [u8; 254], C = Crc8> {
+    ser_msg: SerMsgBase<B, C>,
+    frames: S,
+    overflow: Overflow,
+    counters: QueueCounters,
+}
+
+impl<S: FrameStore> QueuedSerMsg<S> {
+    /// Returns a QueuedSerMsg storing the messages in `frames`, parsed by a [SerMsg::new()][new]
+    ///
+    /// [new]: SerMsg::new()
+    pub fn new(frames: S, overflow: Overflow) -> QueuedSerMsg<S> {
+        QueuedSerMsg::with_parser(SerMsg::new(), frames, overflow)
+    }
+}
+
+impl<S: FrameStore, B: AsRef<[u8]> + AsMut<[u8]>, C: Crc> QueuedSerMsg<S, B, C> {
+    /// Returns a QueuedSerMsg storing the messages parsed by `ser_msg` in `frames`
+    pub fn with_parser(
+        ser_msg: SerMsgBase<B, C>,
+        frames: S,
+        overflow: Overflow,
+    ) -> QueuedSerMsg<S, B, C> {
+        QueuedSerMsg {
+            ser_msg,
+            frames,
+            overflow,
+            counters: QueueCounters::default(),
+        }
+    }
+
+    /// Returns the parser, e.g. to read its configuration
+    pub fn parser(&self) -> &SerMsgBase<B, C> {
+        &self.ser_msg
+    }
+
+    /// Returns the parser, e.g. to enable the resync or a timeout
+    pub fn parser_mut(&mut self) -> &mut SerMsgBase<B, C> {
+        &mut self.ser_msg
+    }
+
+    /// Parses all bytes of the slice and moves its start past them, completed messages are queued.
+    /// With [Overflow::Error][Error] parsing stops when the queue is full and
+    /// [QueueFull][QueueFull] is returned, the remaining bytes are left in the slice.
+    ///
+    /// [Error]: Overflow::Error
+    /// [QueueFull]: Error::QueueFull
+    pub fn parse(&mut self, bytes: &mut &[u8]) -> Result<(), Error> {
+        while !bytes.is_empty() || self.ser_msg.has_resync_bytes() {
+            if self.is_full() && self.overflow == Overflow::Error {
+                return Err(Error::QueueFull);
+            }
+            match self.ser_msg.parse(bytes) {
+                Ok(Some(_)) => self.queue_frame(),
+                Ok(None) => (),
+                Err(_) => self.counters.errors = self.counters.errors.wrapping_add(1),
+            }
+        }
+        Ok(())
+    }
+
+    fn queue_frame(&mut self) {
+        self.counters.received = self.counters.received.wrapping_add(1);
+        // A storage without room or with a shorter payload capacity can't keep the message
+        if self.frames.capacity() == 0
+            || self.ser_msg.return_read_data().len() > self.frames.max_payload()
+        {
+            self.counters.dropped = self.counters.dropped.wrapping_add(1);
+            return;
+        }
+        if self.is_full() {
+            self.counters.dropped = self.counters.dropped.wrapping_add(1);
+            match self.overflow {
+                Overflow::DropOldest => {
+                    self.frames.pop_front();
+                }
+                _ => return,
+            }
+        }
+        self.frames.push_back(FrameRef {
+            id: self.ser_msg.return_msg_id(),
+            payload: self.ser_msg.return_read_data(),
+        });
+    }
+
+    /// Removes and returns the oldest message
+    pub fn pop(&mut self) -> Option<S::Frame> {
+        self.frames.pop_front()
+    }
+
+    /// Returns the oldest message without removing it
+    pub fn front(&self) -> Option<FrameRef<'_>> {
+        self.frames.front()
+    }
+
+    /// Removes all queued messages
+    pub fn clear(&mut self) {
+        while self.frames.pop_front().is_some() {}
+    }
+
+    /// Returns the amount of queued messages
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns true if no message is queued
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns true if the queue is full
+    pub fn is_full(&self) -> bool {
+        self.frames.len() >= self.frames.capacity()
+    }
+
+    /// Returns the [Overflow][Overflow] policy
+    ///
+    /// [Overflow]: Overflow
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// Sets the [Overflow][Overflow] policy
+    ///
+    /// [Overflow]: Overflow
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
+    }
+
+    /// Returns the [QueueCounters][QueueCounters]
+    ///
+    /// [QueueCounters]: QueueCounters
+    pub fn counters(&self) -> QueueCounters {
+        self.counters
+    }
+
+    /// Sets all counters to zero
+    pub fn reset_counters(&mut self) {
+        self.counters = QueueCounters::default();
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="alloc")] {
+        use alloc::collections::VecDeque;
+        use crate::Message;
+
+        /// A [VecDeque] of at most `limit` owned [Message][Message]s.
+        ///
+        /// [Message]: Message
+        #[derive(Debug, Clone)]
+        pub struct FrameDeque {
+            frames: VecDeque<Message>,
+            limit: usize,
+        }
+
+        impl FrameDeque {
+            /// Returns an empty FrameDeque holding at most `limit` messages
+            ///
+            /// # Panics
+            ///
+            /// Panics if `limit` is 0, a FrameDeque needs room for at least one message.
+            pub fn new(limit: usize) -> FrameDeque {
+                assert!(limit > 0, "the deque needs room for at least one message");
+                FrameDeque {
+                    frames: VecDeque::new(),
+                    limit,
+                }
+            }
+        }
+
+        impl FrameStore for FrameDeque {
+            type Frame = Message;
+
+            fn capacity(&self) -> usize {
+                self.limit
+            }
+
+            fn len(&self) -> usize {
+                self.frames.len()
+            }
+
+            fn push_back(&mut self, frame: FrameRef<'_>) {
+                self.frames.push_back(Message::from_parsed(frame));
+            }
+
+            fn pop_front(&mut self) -> Option<Message> {
+                self.frames.pop_front()
+            }
+
+            fn front(&self) -> Option<FrameRef<'_>> {
+                self.frames.front().map(|msg| FrameRef {
+                    id: msg.id,
+                    payload: &msg.payload,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Reading received payloads, similar to `rxObj()` of the Arduino SerialTransfer library.
+
+use crate::{Crc, Error, FrameRef, SerMsgBase};
+
+/// Cursor over a received payload that reads bytes or objects with bounds checks.
+///
+/// Every read starts at the current position and moves the position past the read bytes.
+/// The `_at` variants read at an explicit index, like `rxObj()` on the Arduino, and also move the
+/// position past the read bytes, so following reads continue after them.
+/// With the `zerocopy` feature, objects can be read with [PayloadReader.get()][get].
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{ParseState, SerMsg};
+///
+/// let send_msg = SerMsg::encode_vec(&[1, 2, 3, 4, 5], 1).unwrap();
+/// let mut ser_msg = SerMsg::new();
+/// let (parse_state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+/// if let ParseState::DataReady = parse_state {
+///     let mut reader = ser_msg.payload_reader();
+///     assert_eq!(reader.get_bytes(2), Ok(&[1, 2][..]));
+///     assert_eq!(reader.remaining(), 3);
+///     assert_eq!(reader.get_bytes_at(4, 1), Ok(&[5][..]));
+///     assert!(reader.get_bytes(1).is_err());
+/// }
+/// ```
+///
+/// [get]: PayloadReader::get()
+#[derive(Debug, Clone)]
+pub struct PayloadReader<'a> {
+    data: &'a [u8],
+    ind: usize,
+}
+
+impl<'a> PayloadReader<'a> {
+    /// Returns a new PayloadReader starting at the beginning of the payload
+    pub fn new(data: &'a [u8]) -> PayloadReader<'a> {
+        PayloadReader { data, ind: 0 }
+    }
+
+    /// Returns the current position in the payload
+    pub fn position(&self) -> usize {
+        self.ind
+    }
+
+    /// Sets the current position in the payload, it may be past the end of the payload
+    pub fn set_position(&mut self, ind: usize) {
+        self.ind = ind;
+    }
+
+    /// Returns the amount of bytes left after the current position
+    pub fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.ind)
+    }
+
+    /// Reads `len` bytes at the current position.
+    /// Returns [OutOfBounds][OutOfBounds] if the bytes exceed the payload.
+    ///
+    /// [OutOfBounds]: Error::OutOfBounds
+    pub fn get_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        self.get_bytes_at(self.ind, len)
+    }
+
+    /// Reads `len` bytes starting at `index`.
+    /// Returns [OutOfBounds][OutOfBounds] if the bytes exceed the payload.
+    ///
+    /// [OutOfBounds]: Error::OutOfBounds
+    pub fn get_bytes_at(&mut self, index: usize, len: usize) -> Result<&'a [u8], Error> {
+        let bytes = index
+            .checked_add(len)
+            .and_then(|end| self.data.get(index..end))
+            .ok_or(Error::OutOfBounds {
+                index,
+                len,
+                payload_len: self.data.len(),
+            })?;
+        self.ind = index + len;
+        Ok(bytes)
+    }
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>, C: Crc> SerMsgBase<B, C> {
+    /// Returns a [PayloadReader][PayloadReader] over the data of the parsed message, should only
+    /// be used after the [ParseState][ParseState] is [DataReady][DataReady]
+    ///
+    /// [PayloadReader]: PayloadReader
+    /// [ParseState]: crate::ParseState
+    /// [DataReady]: crate::ParseState::DataReady
+    pub fn payload_reader(&self) -> PayloadReader<'_> {
+        PayloadReader::new(self.return_read_data())
+    }
+}
+
+impl<'a> FrameRef<'a> {
+    /// Returns a [PayloadReader][PayloadReader] over the payload of the message
+    ///
+    /// [PayloadReader]: PayloadReader
+    pub fn reader(&self) -> PayloadReader<'a> {
+        PayloadReader::new(self.payload)
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="zerocopy")] {
+        use zerocopy::FromBytes;
+
+        impl PayloadReader<'_> {
+            /// Reads an object at the current position.
+            /// Returns [OutOfBounds][OutOfBounds] if the object exceeds the payload.
+            ///
+            /// # Example
+            ///
+            /// ```rust
+            /// use serialmessage::{FrameBuilder, PayloadReader};
+            /// use zerocopy::{AsBytes, FromBytes};
+            ///
+            /// #[repr(C, packed)]
+            /// #[derive(AsBytes, FromBytes, Clone, Copy, Debug, PartialEq)]
+            /// struct Header {
+            ///     kind: u8,
+            ///     count: u16,
+            /// }
+            ///
+            /// let mut builder = FrameBuilder::new();
+            /// builder.put(&Header { kind: 1, count: 2 }).unwrap();
+            /// builder.put(&1.5_f32).unwrap();
+            ///
+            /// let mut reader = PayloadReader::new(builder.payload());
+            /// assert_eq!(reader.get::<Header>(), Ok(Header { kind: 1, count: 2 }));
+            /// assert_eq!(reader.get::<f32>(), Ok(1.5));
+            /// assert_eq!(reader.get_at::<u8>(0), Ok(1));
+            /// ```
+            ///
+            /// [OutOfBounds]: Error::OutOfBounds
+            pub fn get<T: FromBytes>(&mut self) -> Result<T, Error> {
+                self.get_at(self.ind)
+            }
+
+            /// Reads an object starting at `index`, like `rxObj()` of the Arduino library.
+            /// Returns [OutOfBounds][OutOfBounds] if the object exceeds the payload.
+            ///
+            /// [OutOfBounds]: Error::OutOfBounds
+            pub fn get_at<T: FromBytes>(&mut self, index: usize) -> Result<T, Error> {
+                let bytes = self.get_bytes_at(index, core::mem::size_of::<T>())?;
+                // The length always matches the size of T, so reading can't fail
+                Ok(T::read_from(bytes).unwrap())
+            }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Receiving messages in an interrupt and handling them in the main loop.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{Crc, Error, FrameRef, QueueCounters, QueuedFrame, SerMsgBase};
+
+/// A lock-free single producer single consumer queue of `N` messages with a payload of up to
+/// `P` bytes, shared by an [RxProducer][RxProducer] and an [RxConsumer][RxConsumer] created with
+/// [SerMsg.split()][split].
+///
+/// The queue only uses atomic loads and stores, so it works on every Microcontroller including
+/// those without compare and swap instructions like the Cortex-M0. Like with a
+/// [FrameRing][FrameRing], the payload capacity should match the one of the parser, messages
+/// with a longer payload are dropped. An RxQueue without room for a message fails to compile:
+///
+/// ```rust,compile_fail
+/// let queue = serialmessage::RxQueue::<0>::new();
+/// ```
+///
+/// [RxProducer]: RxProducer
+/// [RxConsumer]: RxConsumer
+/// [split]: SerMsgBase::split()
+/// [FrameRing]: crate::FrameRing
+pub struct RxQueue<const N: usize, const P: usize = 254> {
+    frames: [UnsafeCell<QueuedFrame<P>>; N],
+    // Both indices count up to 2 * N, so a full queue can be told apart from an empty one
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// SAFETY: The producer only writes the slot at the tail before publishing it by moving the tail,
+// the consumer only reads the slots between the head and the tail before releasing them by moving
+// the head. Splitting borrows the queue mutably, so there is only one producer and one consumer.
+unsafe impl<const N: usize, const P: usize> Sync for RxQueue<N, P> {}
+
+impl<const N: usize, const P: usize> Default for RxQueue<N, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const P: usize> RxQueue<N, P> {
+    // Evaluated by new(), fails the build for a queue without room for a message
+    const NOT_EMPTY: () = assert!(N > 0, "the queue needs room for at least one message");
+
+    /// Returns an empty RxQueue, can be used to initialize a static
+    pub const fn new() -> RxQueue<N, P> {
+        let () = Self::NOT_EMPTY;
+        RxQueue {
+            frames: [const { UnsafeCell::new(QueuedFrame::EMPTY) }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the amount of queued messages
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        (tail + 2 * N - head) % (2 * N)
+    }
+
+    /// Returns true if no message is queued
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn next_ind(ind: usize) -> usize {
+        (ind + 1) % (2 * N)
+    }
+}
+
+/// The half of a split [SerMsg][SerMsg] parsing the received bytes, e.g. in the UART interrupt.
+///
+/// Completed messages are pushed to the [RxQueue][RxQueue]. The producer never waits for the
+/// consumer, if the queue is full the completed message is dropped and counted.
+///
+/// [SerMsg]: crate::SerMsg
+/// [RxQueue]: RxQueue
+pub struct RxProducer<'a, B, C, const N: usize, const P: usize = 254> {
+    ser_msg: &'a mut SerMsgBase<B, C>,
+    queue: &'a RxQueue<N, P>,
+    counters: QueueCounters,
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>, C: Crc, const N: usize, const P: usize>
+    RxProducer<'_, B, C, N, P>
+{
+    /// Parses a received byte. Returns Ok(true) if it completed a message, which was pushed to
+    /// the queue, and [QueueFull][QueueFull] if the message was dropped because the queue is
+    /// full or [PayloadTooLarge][PayloadTooLarge] if its payload exceeds the payload capacity of
+    /// the queue. With the resync enabled a byte can complete several messages, the result of
+    /// the last one is returned.
+    ///
+    /// [QueueFull]: Error::QueueFull
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    pub fn push_byte(&mut self, val: u8) -> Result<bool, Error> {
+        let mut bytes = core::slice::from_ref(&val);
+        let mut result = Ok(false);
+        while !bytes.is_empty() || self.ser_msg.has_resync_bytes() {
+            result = match self.ser_msg.parse(&mut bytes) {
+                Ok(Some(_)) => self.push_frame().map(|_| true),
+                Ok(None) => continue,
+                Err(e) => {
+                    self.counters.errors = self.counters.errors.wrapping_add(1);
+                    Err(e)
+                }
+            };
+        }
+        result
+    }
+
+    /// Parses all received bytes and pushes the completed messages to the queue.
+    /// Dropped and failed messages are only counted.
+    pub fn push_bytes(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() || self.ser_msg.has_resync_bytes() {
+            match self.ser_msg.parse(&mut bytes) {
+                Ok(Some(_)) => {
+                    let _ = self.push_frame();
+                }
+                Ok(None) => (),
+                Err(_) => self.counters.errors = self.counters.errors.wrapping_add(1),
+            }
+        }
+    }
+
+    fn push_frame(&mut self) -> Result<(), Error> {
+        self.counters.received = self.counters.received.wrapping_add(1);
+        let len = self.ser_msg.return_read_data().len();
+        if len > P {
+            self.counters.dropped = self.counters.dropped.wrapping_add(1);
+            return Err(Error::PayloadTooLarge { len });
+        }
+        let tail = self.queue.tail.load(Ordering::Relaxed);
+        let head = self.queue.head.load(Ordering::Acquire);
+        if (tail + 2 * N - head) % (2 * N) >= N {
+            self.counters.dropped = self.counters.dropped.wrapping_add(1);
+            return Err(Error::QueueFull);
+        }
+        // SAFETY: The slot at the tail isn't visible to the consumer until the tail is moved
+        let queued = unsafe { &mut *self.queue.frames[tail % N].get() };
+        queued.set(FrameRef {
+            id: self.ser_msg.return_msg_id(),
+            payload: self.ser_msg.return_read_data(),
+        });
+        self.queue
+            .tail
+            .store(RxQueue::<N, P>::next_ind(tail), Ordering::Release);
+        Ok(())
+    }
+
+    /// Returns the parser, e.g. to enable the resync
+    pub fn parser_mut(&mut self) -> &mut SerMsgBase<B, C> {
+        self.ser_msg
+    }
+
+    /// Returns the [QueueCounters][QueueCounters] of the received messages
+    ///
+    /// [QueueCounters]: QueueCounters
+    pub fn counters(&self) -> QueueCounters {
+        self.counters
+    }
+}
+
+/// The half of a split [SerMsg][SerMsg] popping the completed messages, e.g. in the main loop.
+///
+/// [SerMsg]: crate::SerMsg
+pub struct RxConsumer<'a, const N: usize, const P: usize = 254> {
+    queue: &'a RxQueue<N, P>,
+}
+
+impl<const N: usize, const P: usize> RxConsumer<'_, N, P> {
+    /// Removes and returns the oldest message
+    pub fn pop(&mut self) -> Option<QueuedFrame<P>> {
+        let head = self.queue.head.load(Ordering::Relaxed);
+        let frame = self.queued_frame(head)?.clone();
+        self.queue
+            .head
+            .store(RxQueue::<N, P>::next_ind(head), Ordering::Release);
+        Some(frame)
+    }
+
+    /// Returns the oldest message without removing it
+    pub fn front(&self) -> Option<FrameRef<'_>> {
+        let head = self.queue.head.load(Ordering::Relaxed);
+        self.queued_frame(head).map(QueuedFrame::as_frame_ref)
+    }
+
+    fn queued_frame(&self, head: usize) -> Option<&QueuedFrame<P>> {
+        if head == self.queue.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: The slot at the head was published by the producer and isn't written again
+        // until the head is moved past it
+        Some(unsafe { &*self.queue.frames[head % N].get() })
+    }
+
+    /// Returns the amount of queued messages
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns true if no message is queued
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>, C: Crc> SerMsgBase<B, C> {
+    /// Splits the parser into an [RxProducer][RxProducer] parsing the received bytes and an
+    /// [RxConsumer][RxConsumer] popping the completed messages from the [RxQueue][RxQueue].
+    ///
+    /// The producer can be moved into an interrupt handler, e.g. inside a
+    /// `critical_section::Mutex`, while the consumer stays in the main loop. Only the producer
+    /// touches the parser, so the main loop pops messages without a critical section and never
+    /// blocks the interrupt.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use core::cell::RefCell;
+    /// use critical_section::Mutex;
+    /// use serialmessage::{Crc8, RxProducer, RxQueue, SerMsg};
+    ///
+    /// type Producer = RxProducer<'static, [u8; 254], Crc8, 4>;
+    /// static PRODUCER: Mutex<RefCell<Option<Producer>>> = Mutex::new(RefCell::new(None));
+    ///
+    /// // The UART RX interrupt
+    /// fn on_rx_byte(val: u8) {
+    ///     critical_section::with(|cs| {
+    ///         if let Some(producer) = PRODUCER.borrow_ref_mut(cs).as_mut() {
+    ///             let _ = producer.push_byte(val);
+    ///         }
+    ///     });
+    /// }
+    ///
+    /// let ser_msg: &'static mut SerMsg = Box::leak(Box::new(SerMsg::new()));
+    /// let queue: &'static mut RxQueue<4> = Box::leak(Box::new(RxQueue::new()));
+    /// let (producer, mut consumer) = ser_msg.split(queue);
+    /// critical_section::with(|cs| PRODUCER.borrow(cs).replace(Some(producer)));
+    ///
+    /// for val in SerMsg::encode_vec(&[1, 2, 3], 1).unwrap() {
+    ///     on_rx_byte(val);
+    /// }
+    /// let frame = consumer.pop().unwrap();
+    /// assert_eq!(frame.payload(), &[1, 2, 3]);
+    /// ```
+    ///
+    /// [RxProducer]: RxProducer
+    /// [RxConsumer]: RxConsumer
+    /// [RxQueue]: RxQueue
+    pub fn split<'a, const N: usize, const P: usize>(
+        &'a mut self,
+        queue: &'a mut RxQueue<N, P>,
+    ) -> (RxProducer<'a, B, C, N, P>, RxConsumer<'a, N, P>) {
+        let queue = &*queue;
+        (
+            RxProducer {
+                ser_msg: self,
+                queue,
+                counters: QueueCounters::default(),
+            },
+            RxConsumer { queue },
+        )
+    }
+}
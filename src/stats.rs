@@ -0,0 +1,329 @@
+//! Monitoring the health of a link over a long time.
+
+use crate::{Clock, Crc, Error, FrameRef, ParseState, SerMsg, SerMsgBase, TickClock};
+
+/// Statistics of a link, collected by the parse functions ending in `_stats` like
+/// [SerMsg.parse_read_bytes_stats()][parse_read_bytes_stats] and, for the sent messages, by the
+/// encode functions ending in `_stats` like [SerMsg::encode_into_stats()][encode_into_stats]
+/// or by [LinkStats.record_sent()][record_sent].
+///
+/// The statistics don't allocate, a snapshot is taken by cloning them.
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::{LinkStats, SerMsg};
+///
+/// let mut stats = LinkStats::new();
+/// let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+/// stats.record_sent(&send_msg);
+///
+/// let mut rcvd_bytes = vec![0, 0];
+/// rcvd_bytes.extend(&send_msg);
+/// let mut ser_msg = SerMsg::new();
+/// ser_msg.parse_read_bytes_stats(&rcvd_bytes, &mut stats);
+///
+/// let snapshot = stats.clone();
+/// assert_eq!(snapshot.frames_ok, 1);
+/// assert_eq!(snapshot.frames_with_id(1), 1);
+/// assert_eq!(snapshot.discarded_bytes, 2);
+/// assert_eq!(snapshot.error_rate(), 0.0);
+/// stats.reset();
+/// ```
+///
+/// [parse_read_bytes_stats]: crate::SerMsg::parse_read_bytes_stats()
+/// [encode_into_stats]: crate::SerMsg::encode_into_stats()
+/// [record_sent]: LinkStats::record_sent()
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkStats {
+    /// The amount of messages parsed successfully
+    pub frames_ok: u32,
+    /// The amount of [CrcErrors][CrcError]
+    ///
+    /// [CrcError]: ParseState::CrcError
+    pub crc_errors: u32,
+    /// The amount of [HighPayloadErrors][HighPayloadError]
+    ///
+    /// [HighPayloadError]: ParseState::HighPayloadError
+    pub high_payload_errors: u32,
+    /// The amount of [StopByteErrors][StopByteError]
+    ///
+    /// [StopByteError]: ParseState::StopByteError
+    pub stop_byte_errors: u32,
+    /// The amount of [COBSErrors][COBSError]
+    ///
+    /// [COBSError]: ParseState::COBSError
+    pub cobs_errors: u32,
+    /// The amount of [StalePacketErrors][StalePacketError]
+    ///
+    /// [StalePacketError]: ParseState::StalePacketError
+    pub stale_errors: u32,
+    /// The amount of received bytes that were not part of a message
+    pub discarded_bytes: u64,
+    /// The amount of received bytes
+    pub bytes_received: u64,
+    /// The amount of sent messages
+    pub frames_sent: u32,
+    /// The amount of sent bytes
+    pub bytes_sent: u64,
+    frames_by_id: [u32; 256],
+    // One bit per parsed message, set if it failed, the newest message is the lowest bit
+    recent_errors: u64,
+    recent_len: u8,
+}
+
+impl Default for LinkStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LinkStats {
+    /// The amount of the most recent messages the [error rate][error_rate] is calculated from
+    ///
+    /// [error_rate]: LinkStats::error_rate()
+    pub const ERROR_RATE_WINDOW: usize = 64;
+
+    /// Returns statistics with all counters set to 0, can be used to initialize a static
+    pub const fn new() -> LinkStats {
+        LinkStats {
+            frames_ok: 0,
+            crc_errors: 0,
+            high_payload_errors: 0,
+            stop_byte_errors: 0,
+            cobs_errors: 0,
+            stale_errors: 0,
+            discarded_bytes: 0,
+            bytes_received: 0,
+            frames_sent: 0,
+            bytes_sent: 0,
+            frames_by_id: [0; 256],
+            recent_errors: 0,
+            recent_len: 0,
+        }
+    }
+
+    /// Sets all counters to 0
+    pub fn reset(&mut self) {
+        *self = LinkStats::new();
+    }
+
+    /// Returns the amount of messages with the packet id parsed successfully
+    pub fn frames_with_id(&self, id: u8) -> u32 {
+        self.frames_by_id[id as usize]
+    }
+
+    /// Returns the amount of failed messages, the sum of all error counters
+    pub fn frame_errors(&self) -> u32 {
+        self.crc_errors
+            .wrapping_add(self.high_payload_errors)
+            .wrapping_add(self.stop_byte_errors)
+            .wrapping_add(self.cobs_errors)
+            .wrapping_add(self.stale_errors)
+    }
+
+    /// Returns the share of failed messages among the last
+    /// [ERROR_RATE_WINDOW][ERROR_RATE_WINDOW] parsed messages, from 0.0 to 1.0.
+    /// Returns 0.0 if no message was parsed yet.
+    ///
+    /// [ERROR_RATE_WINDOW]: LinkStats::ERROR_RATE_WINDOW
+    pub fn error_rate(&self) -> f32 {
+        match self.recent_len {
+            0 => 0.0,
+            len => self.recent_errors.count_ones() as f32 / len as f32,
+        }
+    }
+
+    /// Counts a sent message, e.g. one returned by [SerMsg::encode_vec()][encode_vec]
+    ///
+    /// [encode_vec]: crate::SerMsg::encode_vec()
+    pub fn record_sent(&mut self, msg: &[u8]) {
+        self.frames_sent = self.frames_sent.wrapping_add(1);
+        self.bytes_sent = self.bytes_sent.wrapping_add(msg.len() as u64);
+    }
+
+    fn record_frame(&mut self, frame: FrameRef<'_>) {
+        self.frames_ok = self.frames_ok.wrapping_add(1);
+        let frames = &mut self.frames_by_id[frame.id as usize];
+        *frames = frames.wrapping_add(1);
+        self.record_recent(false);
+    }
+
+    fn record_error(&mut self, e: Error) {
+        let errors = match e {
+            Error::Crc { .. } => &mut self.crc_errors,
+            Error::HighPayload { .. } => &mut self.high_payload_errors,
+            Error::StopByte { .. } => &mut self.stop_byte_errors,
+            Error::Cobs => &mut self.cobs_errors,
+            Error::Stale => &mut self.stale_errors,
+            _ => return,
+        };
+        *errors = errors.wrapping_add(1);
+        self.record_recent(true);
+    }
+
+    fn record_recent(&mut self, failed: bool) {
+        self.recent_errors = (self.recent_errors << 1) | failed as u64;
+        if (self.recent_len as usize) < LinkStats::ERROR_RATE_WINDOW {
+            self.recent_len += 1;
+        }
+    }
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>, C: Crc> SerMsgBase<B, C> {
+    /// Same as [SerMsg.parse_read_bytes()][parse_read_bytes], but additionally
+    /// collects the [LinkStats][LinkStats] of the parsed bytes.
+    ///
+    /// [parse_read_bytes]: crate::SerMsg::parse_read_bytes()
+    /// [LinkStats]: LinkStats
+    pub fn parse_read_bytes_stats(
+        &mut self,
+        arr: &[u8],
+        stats: &mut LinkStats,
+    ) -> (ParseState, usize) {
+        let (result, parsed_bytes) = self.parse_bytes_stats::<TickClock>(arr, None, stats);
+        (ParseState::from_result(result), parsed_bytes)
+    }
+
+    /// Same as [SerMsg.parse_read_bytes_timed()][parse_read_bytes_timed], but additionally
+    /// collects the [LinkStats][LinkStats] of the parsed bytes, including the stale messages.
+    ///
+    /// [parse_read_bytes_timed]: crate::SerMsg::parse_read_bytes_timed()
+    /// [LinkStats]: LinkStats
+    pub fn parse_read_bytes_timed_stats<K: Clock>(
+        &mut self,
+        arr: &[u8],
+        clock: &K,
+        stats: &mut LinkStats,
+    ) -> (ParseState, usize) {
+        let (result, parsed_bytes) = self.parse_bytes_stats(arr, Some(clock), stats);
+        (ParseState::from_result(result), parsed_bytes)
+    }
+
+    /// Same as [SerMsg.parse()][parse], but additionally collects the [LinkStats][LinkStats]
+    /// of the parsed bytes.
+    ///
+    /// [parse]: crate::SerMsg::parse()
+    /// [LinkStats]: LinkStats
+    pub fn parse_stats(
+        &mut self,
+        bytes: &mut &[u8],
+        stats: &mut LinkStats,
+    ) -> Result<Option<FrameRef<'_>>, Error> {
+        let (result, parsed_bytes) = self.parse_bytes_stats::<TickClock>(bytes, None, stats);
+        *bytes = &bytes[parsed_bytes..];
+        self.frame_from_result(result)
+    }
+
+    /// Same as [SerMsg.parse_timed()][parse_timed], but additionally collects the
+    /// [LinkStats][LinkStats] of the parsed bytes, including the stale messages.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serialmessage::{Error, LinkStats, SerMsg, TickClock};
+    ///
+    /// let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+    /// let clock = TickClock::new();
+    /// let mut stats = LinkStats::new();
+    /// let mut ser_msg = SerMsg::new();
+    /// ser_msg.set_timeout(Some(50));
+    ///
+    /// // The message gets cut off and the rest never arrives
+    /// let mut bytes = &send_msg[..4];
+    /// assert_eq!(ser_msg.parse_timed_stats(&mut bytes, &clock, &mut stats), Ok(None));
+    ///
+    /// clock.advance(50);
+    /// let mut bytes = &[][..];
+    /// let result = ser_msg.parse_timed_stats(&mut bytes, &clock, &mut stats);
+    /// assert_eq!(result, Err(Error::Stale));
+    /// assert_eq!(stats.stale_errors, 1);
+    /// ```
+    ///
+    /// [parse_timed]: crate::SerMsg::parse_timed()
+    /// [LinkStats]: LinkStats
+    pub fn parse_timed_stats<K: Clock>(
+        &mut self,
+        bytes: &mut &[u8],
+        clock: &K,
+        stats: &mut LinkStats,
+    ) -> Result<Option<FrameRef<'_>>, Error> {
+        let (result, parsed_bytes) = self.parse_bytes_stats(bytes, Some(clock), stats);
+        *bytes = &bytes[parsed_bytes..];
+        self.frame_from_result(result)
+    }
+
+    fn parse_bytes_stats<K: Clock>(
+        &mut self,
+        arr: &[u8],
+        clock: Option<&K>,
+        stats: &mut LinkStats,
+    ) -> (Result<bool, Error>, usize) {
+        let discarded_bytes = self.discarded_bytes;
+        let (result, parsed_bytes) = self.parse_bytes(arr, clock);
+        stats.bytes_received = stats.bytes_received.wrapping_add(parsed_bytes as u64);
+        stats.discarded_bytes = stats
+            .discarded_bytes
+            .wrapping_add(self.discarded_bytes.wrapping_sub(discarded_bytes));
+        match result {
+            Ok(true) => stats.record_frame(self.read_frame()),
+            Ok(false) => (),
+            Err(e) => stats.record_error(e),
+        }
+        (result, parsed_bytes)
+    }
+}
+
+impl SerMsg {
+    /// Same as [SerMsg::encode_into()][encode_into], but additionally counts the message
+    /// in the [LinkStats][LinkStats] if it was created.
+    ///
+    /// [encode_into]: SerMsg::encode_into()
+    /// [LinkStats]: LinkStats
+    pub fn encode_into_stats(
+        data: &[u8],
+        id: u8,
+        out: &mut [u8],
+        stats: &mut LinkStats,
+    ) -> Result<usize, Error> {
+        let msg_len = SerMsg::encode_into(data, id, out)?;
+        stats.record_sent(&out[..msg_len]);
+        Ok(msg_len)
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="alloc")] {
+        use alloc::vec::Vec;
+
+        impl SerMsg {
+            /// Same as [SerMsg::encode_extend()][encode_extend], but additionally counts the
+            /// message in the [LinkStats][LinkStats] if it was created.
+            ///
+            /// # Example
+            ///
+            /// ```rust
+            /// use serialmessage::{LinkStats, SerMsg};
+            ///
+            /// let mut stats = LinkStats::new();
+            /// let mut send_buf = Vec::new();
+            /// let msg_len = SerMsg::encode_extend_stats(&[1, 2, 3], 1, &mut send_buf, &mut stats).unwrap();
+            /// assert_eq!(stats.frames_sent, 1);
+            /// assert_eq!(stats.bytes_sent, msg_len as u64);
+            /// ```
+            ///
+            /// [encode_extend]: SerMsg::encode_extend()
+            /// [LinkStats]: LinkStats
+            pub fn encode_extend_stats(
+                data: &[u8],
+                id: u8,
+                data_vec: &mut Vec<u8>,
+                stats: &mut LinkStats,
+            ) -> Result<usize, Error> {
+                let msg_len = SerMsg::encode_extend(data, id, data_vec)?;
+                stats.record_sent(&data_vec[data_vec.len() - msg_len..]);
+                Ok(msg_len)
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Sending and receiving messages over a blocking [std::io] stream, like the Arduino SerialTransfer library.
+
+use std::io::{self, Read, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::chunks::RxChunks;
+use crate::{Error, FrameRef, FramingConfig, Message, SerMsg, StdClock};
+
+/// Sends and receives messages over a stream implementing [Read] and [Write], e.g. a serial port
+/// of the `serialport` crate, mirroring the `SerialTransfer` class of the Arduino library.
+///
+/// Errors of the stream are returned as they are. Messages that fail to parse are returned by
+/// [Transfer.available()][available] as an [io::Error] of the kind [InvalidData][InvalidData]
+/// wrapping the [Error][Error] of the parser, while [Transfer.receive()][receive] skips and
+/// counts them. Failures to create a message are returned as [InvalidInput][InvalidInput].
+///
+/// # Example
+///
+/// ```rust
+/// use serialmessage::Transfer;
+/// use std::io::Cursor;
+/// use std::time::Duration;
+///
+/// // Stands in for a serial port receiving a message
+/// let port = Cursor::new(serialmessage::SerMsg::encode_vec(&[1, 2, 3], 1).unwrap());
+///
+/// let mut transfer = Transfer::new(port);
+/// let msg = transfer.receive(Duration::from_millis(100)).unwrap();
+/// assert_eq!((msg.id, &msg.payload[..]), (1, &[1, 2, 3][..]));
+/// ```
+///
+/// [available]: Transfer::available()
+/// [InvalidData]: io::ErrorKind::InvalidData
+/// [Error]: crate::Error
+/// [receive]: Transfer::receive()
+/// [InvalidInput]: io::ErrorKind::InvalidInput
+pub struct Transfer<T> {
+    port: T,
+    config: FramingConfig,
+    ser_msg: SerMsg,
+    clock: StdClock,
+    rx_chunks: RxChunks<256>,
+    skipped_frames: u32,
+}
+
+impl<T: Read + Write> Transfer<T> {
+    /// The time [Transfer.receive()][receive] waits before reading again if no bytes arrived
+    ///
+    /// [receive]: Transfer::receive()
+    pub const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+    /// Returns a new Transfer sending and receiving over the stream
+    pub fn new(port: T) -> Transfer<T> {
+        Transfer::with_config(port, FramingConfig::DEFAULT)
+    }
+
+    /// Returns a new Transfer sending and receiving over the stream
+    /// using the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: FramingConfig
+    pub fn with_config(port: T, config: FramingConfig) -> Transfer<T> {
+        Transfer {
+            port,
+            config,
+            ser_msg: SerMsg::with_config(config),
+            clock: StdClock::new(),
+            rx_chunks: RxChunks::new(),
+            skipped_frames: 0,
+        }
+    }
+
+    /// Returns the stream
+    pub fn port(&self) -> &T {
+        &self.port
+    }
+
+    /// Returns the stream, e.g. to change its settings
+    pub fn port_mut(&mut self) -> &mut T {
+        &mut self.port
+    }
+
+    /// Returns the stream, dropping the bytes received but not parsed yet
+    pub fn into_inner(self) -> T {
+        self.port
+    }
+
+    /// Returns the parser, e.g. to enable the resync or a timeout
+    pub fn parser_mut(&mut self) -> &mut SerMsg {
+        &mut self.ser_msg
+    }
+
+    /// Packs the slice into the message format and writes the message to the stream,
+    /// like `sendData()` of the Arduino library
+    pub fn send(&mut self, data: &[u8], id: u8) -> io::Result<()> {
+        let msg = self
+            .config
+            .encode_vec(data, id)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        self.port.write_all(&msg)?;
+        self.port.flush()
+    }
+
+    /// Reads the available bytes once and parses them, like `available()` of the Arduino library.
+    /// Returns true if a message was completed, its payload and id are then returned by
+    /// [Transfer.payload()][payload] and [Transfer.packet_id()][packet_id] until this function
+    /// is called again.
+    ///
+    /// A read that times out or would block counts as no bytes being available.
+    ///
+    /// [payload]: Transfer::payload()
+    /// [packet_id]: Transfer::packet_id()
+    pub fn available(&mut self) -> io::Result<bool> {
+        let (result, _read_bytes) = self.poll()?;
+        result.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Waits for the next message until the timeout has passed.
+    /// Returns an [io::Error] of the kind [TimedOut][TimedOut] if no message was completed in time.
+    ///
+    /// Messages that fail to parse, e.g. because of line noise, are skipped and counted by
+    /// [Transfer.skipped_frames()][skipped_frames], so a following message is still received
+    /// before the timeout.
+    ///
+    /// [TimedOut]: io::ErrorKind::TimedOut
+    /// [skipped_frames]: Transfer::skipped_frames()
+    pub fn receive(&mut self, timeout: Duration) -> io::Result<Message> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let (result, read_bytes) = self.poll()?;
+            let completed = result.unwrap_or_else(|_| {
+                self.skipped_frames = self.skipped_frames.wrapping_add(1);
+                false
+            });
+            if completed {
+                return Ok(Message::from_parsed(FrameRef {
+                    id: self.packet_id(),
+                    payload: self.payload(),
+                }));
+            }
+            if Instant::now() >= deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "no message was received within the timeout",
+                ));
+            }
+            if read_bytes == 0 {
+                thread::sleep(Transfer::<T>::POLL_INTERVAL);
+            }
+        }
+    }
+
+    /// Returns the amount of messages [Transfer.receive()][receive] skipped because they
+    /// failed to parse
+    ///
+    /// [receive]: Transfer::receive()
+    pub fn skipped_frames(&self) -> u32 {
+        self.skipped_frames
+    }
+
+    /// Returns the payload of the message completed by [Transfer.available()][available]
+    ///
+    /// [available]: Transfer::available()
+    pub fn payload(&self) -> &[u8] {
+        self.ser_msg.return_read_data()
+    }
+
+    /// Returns the id of the message completed by [Transfer.available()][available],
+    /// like `currentPacketID()` of the Arduino library
+    ///
+    /// [available]: Transfer::available()
+    pub fn packet_id(&self) -> u8 {
+        self.ser_msg.return_msg_id()
+    }
+
+    // Parses the bytes left from the last read, or reads once if all were parsed.
+    // Returns if a message was completed or failed and the amount of bytes read.
+    fn poll(&mut self) -> io::Result<(Result<bool, Error>, usize)> {
+        let mut read_bytes = 0;
+        if self.rx_chunks.is_parsed(&self.ser_msg) {
+            read_bytes = match self.port.read(self.rx_chunks.buf_mut()) {
+                Ok(read_bytes) => read_bytes,
+                Err(e) => match e.kind() {
+                    io::ErrorKind::TimedOut
+                    | io::ErrorKind::WouldBlock
+                    | io::ErrorKind::Interrupted => 0,
+                    _ => return Err(e),
+                },
+            };
+            self.rx_chunks.filled(read_bytes);
+        }
+        let result = self.rx_chunks.parse(&mut self.ser_msg, Some(&self.clock));
+        Ok((result, read_bytes))
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="zerocopy")] {
+        use zerocopy::{AsBytes, FromBytes};
+
+        impl<T: Read + Write> Transfer<T> {
+            /// Writes the object as the payload of a message to the stream,
+            /// like `sendDatum()` of the Arduino library
+            pub fn send_obj<O: AsBytes + ?Sized>(&mut self, obj: &O, id: u8) -> io::Result<()> {
+                self.send(obj.as_bytes(), id)
+            }
+
+            /// Reads an object starting at `index` from the payload of the message completed by
+            /// [Transfer.available()][available], like `rxObj()` of the Arduino library
+            ///
+            /// [available]: Transfer::available()
+            pub fn rx_obj<O: FromBytes>(&self, index: usize) -> Result<O, crate::Error> {
+                self.ser_msg.payload_reader().get_at(index)
+            }
+        }
+    }
+}
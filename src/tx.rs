@@ -0,0 +1,151 @@
+//! Sending messages byte by byte from the transmit interrupt of a UART.
+
+use crate::{Crc, Crc8, Error, FramingConfig, SerMsg};
+
+/// A ring buffer of `N` bytes holding the messages waiting to be sent.
+///
+/// The main loop queues messages with [TxQueue.send()][send], which never blocks and returns
+/// [QueueFull][QueueFull] if the message doesn't fit into the free bytes. The transmit interrupt
+/// writes the bytes returned by [TxQueue.next_byte()][next_byte] into the data register of the
+/// UART and disables itself once it returns None.
+///
+/// # Example
+///
+/// ```rust
+/// use core::cell::RefCell;
+/// use critical_section::Mutex;
+/// use serialmessage::{SerMsg, TxQueue};
+///
+/// static TX_QUEUE: Mutex<RefCell<TxQueue<64>>> = Mutex::new(RefCell::new(TxQueue::new()));
+///
+/// // The TX empty interrupt
+/// fn on_tx_empty() -> Option<u8> {
+///     critical_section::with(|cs| TX_QUEUE.borrow_ref_mut(cs).next_byte())
+/// }
+///
+/// critical_section::with(|cs| TX_QUEUE.borrow_ref_mut(cs).send(&[1, 2, 3], 1)).unwrap();
+/// // Enable the TX empty interrupt
+///
+/// let mut sent_bytes = Vec::new();
+/// while let Some(val) = on_tx_empty() {
+///     sent_bytes.push(val);
+/// }
+/// assert_eq!(sent_bytes, SerMsg::encode_vec(&[1, 2, 3], 1).unwrap());
+/// ```
+///
+/// [send]: TxQueue::send()
+/// [QueueFull]: Error::QueueFull
+/// [next_byte]: TxQueue::next_byte()
+pub struct TxQueue<const N: usize, C = Crc8> {
+    config: FramingConfig,
+    crc: C,
+    buf: [u8; N],
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> Default for TxQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> TxQueue<N> {
+    /// Returns an empty TxQueue, can be used to initialize a static
+    pub const fn new() -> TxQueue<N> {
+        TxQueue::with_config(FramingConfig::DEFAULT)
+    }
+
+    /// Returns an empty TxQueue creating messages using the [FramingConfig][FramingConfig]
+    ///
+    /// [FramingConfig]: FramingConfig
+    pub const fn with_config(config: FramingConfig) -> TxQueue<N> {
+        TxQueue::with_crc(config, Crc8::new())
+    }
+}
+
+impl<const N: usize, C: Crc> TxQueue<N, C> {
+    /// Returns an empty TxQueue creating messages using the [FramingConfig][FramingConfig]
+    /// and the [Crc][Crc]
+    ///
+    /// [FramingConfig]: FramingConfig
+    /// [Crc]: Crc
+    pub const fn with_crc(config: FramingConfig, crc: C) -> TxQueue<N, C> {
+        TxQueue {
+            config,
+            crc,
+            buf: [0; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Packs the slice into the message format and queues the message bytes.
+    /// Returns [PayloadTooLarge][PayloadTooLarge] if the slice exceeds the maximum payload
+    /// length, [BufferTooSmall][BufferTooSmall] if the message is longer than the whole queue
+    /// and [QueueFull][QueueFull] if the message doesn't fit into the free bytes yet.
+    ///
+    /// [PayloadTooLarge]: Error::PayloadTooLarge
+    /// [BufferTooSmall]: Error::BufferTooSmall
+    /// [QueueFull]: Error::QueueFull
+    pub fn send(&mut self, data: &[u8], id: u8) -> Result<(), Error> {
+        self.send_slices(&[data], id)
+    }
+
+    /// Same as [TxQueue.send()][send], but the payload is the concatenation of all slices in `parts`
+    ///
+    /// [send]: TxQueue::send()
+    pub fn send_slices(&mut self, parts: &[&[u8]], id: u8) -> Result<(), Error> {
+        let payload_len = self.config.slices_len(parts)?;
+        let msg_len = SerMsg::encoded_len_crc::<C>(payload_len);
+        if msg_len > N {
+            return Err(Error::BufferTooSmall {
+                len: N,
+                needed: msg_len,
+            });
+        }
+        if msg_len > self.free() {
+            return Err(Error::QueueFull);
+        }
+        let msg = self
+            .config
+            .encode_slices_iter_crc(&mut self.crc, parts, id)?;
+        for val in msg {
+            self.buf[(self.head + self.len) % N] = val;
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Removes and returns the next byte to send, None if all messages were sent
+    pub fn next_byte(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        let val = self.buf[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(val)
+    }
+
+    /// Returns the amount of bytes waiting to be sent
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if all bytes were sent
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the amount of free bytes
+    pub fn free(&self) -> usize {
+        N - self.len
+    }
+
+    /// Drops all bytes waiting to be sent, including those of a partially sent message
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+}
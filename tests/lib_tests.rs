@@ -418,3 +418,2919 @@ mod zerocopy_tests {
         }
     }
 }
+
+#[cfg(test)]
+mod timeout_tests {
+    use super::*;
+
+    #[test]
+    fn stale_packet_error() {
+        let send_data_vec: Vec<u8> = vec![1, 2, 3];
+        let send_msg = SerMsg::create_msg_vec(&send_data_vec, 1).unwrap();
+        let clock = TickClock::new();
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_timeout(Some(50));
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes_timed(&send_msg[..5], &clock);
+
+        match state {
+            ParseState::Continue => {}
+            _ => {
+                panic!()
+            }
+        }
+
+        clock.advance(50);
+        let (state, parsed_bytes) = ser_msg.parse_read_bytes_timed(&send_msg[5..], &clock);
+
+        match state {
+            ParseState::StalePacketError => {
+                assert_eq!(parsed_bytes, 1);
+            }
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn stale_packet_without_bytes() {
+        // The sender went quiet in the middle of the message
+        let send_msg = SerMsg::create_msg_vec(&[1, 2, 3], 1).unwrap();
+        let clock = TickClock::new();
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_timeout(Some(50));
+        ser_msg.parse_read_bytes_timed(&send_msg[..5], &clock);
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes_timed(&[], &clock);
+
+        match state {
+            ParseState::Continue => {}
+            _ => {
+                panic!()
+            }
+        }
+
+        clock.advance(50);
+        let (state, parsed_bytes) = ser_msg.parse_read_bytes_timed(&[], &clock);
+
+        match state {
+            ParseState::StalePacketError => {
+                assert_eq!(parsed_bytes, 0);
+                assert_eq!(ser_msg.state(), FindByte::Start);
+            }
+            _ => {
+                panic!()
+            }
+        }
+        assert_eq!(
+            ser_msg
+                .parse_timed(&mut &[][..], &clock)
+                .map(|frame| frame.is_none()),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn rearm_after_stale_packet() {
+        let send_data_vec: Vec<u8> = vec![1, 2, 3];
+        let send_msg = SerMsg::create_msg_vec(&send_data_vec, 1).unwrap();
+        let clock = TickClock::new();
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_timeout(Some(50));
+        ser_msg.parse_read_bytes_timed(&send_msg[..5], &clock);
+
+        clock.advance(100);
+        let (state, parsed_bytes) = ser_msg.parse_read_bytes_timed(&send_msg, &clock);
+
+        match state {
+            ParseState::StalePacketError => {}
+            _ => {
+                panic!()
+            }
+        }
+
+        let (state, _parsed_bytes) =
+            ser_msg.parse_read_bytes_timed(&send_msg[parsed_bytes..], &clock);
+
+        match state {
+            ParseState::DataReady => {
+                assert_eq!(&send_data_vec, ser_msg.return_read_data());
+            }
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn message_within_timeout() {
+        let send_data_vec: Vec<u8> = vec![1, 2, 3];
+        let send_msg = SerMsg::create_msg_vec(&send_data_vec, 1).unwrap();
+        let clock = TickClock::new();
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_timeout(Some(50));
+        ser_msg.parse_read_bytes_timed(&send_msg[..5], &clock);
+
+        clock.advance(49);
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes_timed(&send_msg[5..], &clock);
+
+        match state {
+            ParseState::DataReady => {
+                assert_eq!(&send_data_vec, ser_msg.return_read_data());
+            }
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn disabled_timeout() {
+        let send_data_vec: Vec<u8> = vec![1, 2, 3];
+        let send_msg = SerMsg::create_msg_vec(&send_data_vec, 1).unwrap();
+        let clock = TickClock::new();
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.parse_read_bytes_timed(&send_msg[..5], &clock);
+
+        clock.advance(u32::MAX / 2);
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes_timed(&send_msg[5..], &clock);
+
+        match state {
+            ParseState::DataReady => {}
+            _ => {
+                panic!()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod resync_tests {
+    use super::*;
+
+    fn parse_all(ser_msg: &mut SerMsg, rcvd_bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut rcvd_data = Vec::new();
+        let mut all_parsed_bytes = 0;
+        while rcvd_bytes.len() > all_parsed_bytes || ser_msg.has_resync_bytes() {
+            let (state, parsed_bytes) = ser_msg.parse_read_bytes(&rcvd_bytes[all_parsed_bytes..]);
+            all_parsed_bytes += parsed_bytes;
+            if let ParseState::DataReady = state {
+                rcvd_data.push(ser_msg.return_read_data().to_vec());
+            }
+        }
+        rcvd_data
+    }
+
+    #[test]
+    fn stray_start_byte() {
+        let send_data_vec: Vec<u8> = vec![1, 2, 3];
+        let mut rcvd_bytes: Vec<u8> = vec![126];
+        rcvd_bytes.extend(SerMsg::create_msg_vec(&send_data_vec, 1).unwrap());
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_resync(true);
+        let (state, parsed_bytes) = ser_msg.parse_read_bytes(&rcvd_bytes);
+
+        match state {
+            ParseState::HighPayloadError => {}
+            _ => {
+                panic!()
+            }
+        }
+
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&rcvd_bytes[parsed_bytes..]);
+
+        match state {
+            ParseState::DataReady => {
+                assert_eq!(&send_data_vec, ser_msg.return_read_data());
+                assert_eq!(1, ser_msg.return_msg_id());
+            }
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn stray_start_byte_without_resync() {
+        let send_data_vec: Vec<u8> = vec![1, 2, 3];
+        let mut rcvd_bytes: Vec<u8> = vec![126];
+        rcvd_bytes.extend(SerMsg::create_msg_vec(&send_data_vec, 1).unwrap());
+
+        let mut ser_msg = SerMsg::new();
+        assert!(parse_all(&mut ser_msg, &rcvd_bytes).is_empty());
+    }
+
+    #[test]
+    fn stray_start_byte_with_cobs() {
+        // The overhead byte of the real message is read as a small payload length,
+        // so the error only shows up at the end of the input
+        let send_data_vec: Vec<u8> = vec![1, 2, 126, 4];
+        let mut rcvd_bytes: Vec<u8> = vec![126];
+        rcvd_bytes.extend(SerMsg::create_msg_vec(&send_data_vec, 1).unwrap());
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_resync(true);
+        assert_eq!(parse_all(&mut ser_msg, &rcvd_bytes), vec![send_data_vec]);
+    }
+
+    #[test]
+    fn stray_start_byte_swallowing_multiple_messages() {
+        let mut rcvd_bytes: Vec<u8> = vec![126, 1, 20, 25];
+        for i in 0..3 {
+            rcvd_bytes.extend(SerMsg::create_msg_vec(&[i, 126, i], i).unwrap());
+        }
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_resync(true);
+        assert_eq!(
+            parse_all(&mut ser_msg, &rcvd_bytes),
+            vec![vec![0, 126, 0], vec![1, 126, 1], vec![2, 126, 2]]
+        );
+    }
+
+    #[test]
+    fn wrong_stopbyte_resync() {
+        let send_data_vec: Vec<u8> = vec![1];
+        let mut rcvd_bytes = SerMsg::create_msg_vec(&send_data_vec, 1).unwrap();
+        rcvd_bytes[6] = 126;
+        rcvd_bytes.extend(SerMsg::create_msg_vec(&send_data_vec, 2).unwrap()[1..].to_vec());
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_resync(true);
+        assert_eq!(parse_all(&mut ser_msg, &rcvd_bytes), vec![send_data_vec]);
+        assert_eq!(2, ser_msg.return_msg_id());
+    }
+}
+
+#[cfg(test)]
+mod frames_tests {
+    use super::*;
+
+    #[test]
+    fn all_frames() {
+        let mut rcvd_bytes: Vec<u8> = Vec::new();
+        for i in 0..3 {
+            rcvd_bytes.extend(SerMsg::create_msg_vec(&[i; 3], i).unwrap());
+        }
+
+        let mut ser_msg = SerMsg::new();
+        let mut frames = ser_msg.frames(&rcvd_bytes);
+        let mut i = 0;
+        while let Some(frame) = frames.next_frame() {
+            assert_eq!(
+                frame,
+                Ok(FrameRef {
+                    id: i,
+                    payload: &[i; 3]
+                })
+            );
+            i += 1;
+        }
+        assert_eq!(i, 3);
+        assert_eq!(frames.parsed_bytes(), rcvd_bytes.len());
+    }
+
+    #[test]
+    fn frames_and_errors() {
+        let mut rcvd_bytes = SerMsg::create_msg_vec(&[1], 1).unwrap();
+        let mut wrong_crc_msg = SerMsg::create_msg_vec(&[2], 2).unwrap();
+        let crc = wrong_crc_msg[5];
+        wrong_crc_msg[5] = 0;
+        rcvd_bytes.extend(wrong_crc_msg);
+        rcvd_bytes.extend(SerMsg::create_msg_vec(&[3], 3).unwrap());
+
+        let mut ser_msg = SerMsg::new();
+        let frames: Vec<Result<Message, Error>> = ser_msg.frames(&rcvd_bytes).collect();
+        assert_eq!(
+            frames,
+            vec![
+                Ok(Message::new(1, &[1]).unwrap()),
+                Err(Error::Crc {
+                    expected: crc.into(),
+                    received: 0
+                }),
+                Ok(Message::new(3, &[3]).unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn frame_split_over_slices() {
+        let send_msg = SerMsg::create_msg_vec(&[1, 2, 3], 1).unwrap();
+
+        let mut ser_msg = SerMsg::new();
+        assert_eq!(ser_msg.frames(&send_msg[..4]).count(), 0);
+
+        let mut rcvd_data = Vec::new();
+        ser_msg.for_each_frame(&send_msg[4..], |frame| {
+            rcvd_data.push(frame.unwrap().payload.to_vec());
+        });
+        assert_eq!(rcvd_data, vec![vec![1, 2, 3]]);
+    }
+}
+
+#[cfg(test)]
+mod decode_tests {
+    use super::*;
+
+    #[test]
+    fn decode_borrowed() {
+        let send_data_vec: Vec<u8> = vec![126, 1, 126, 126, 2];
+        let send_msg = SerMsg::create_msg_vec(&send_data_vec, 3).unwrap();
+
+        let (id, payload) = decode_frame(&send_msg).unwrap();
+        assert_eq!(id, 3);
+        assert_eq!(payload.len(), send_data_vec.len());
+        assert_eq!(payload.to_vec(), send_data_vec);
+
+        let mut data_arr = [0; 254];
+        let data_len = payload.copy_to_slice(&mut data_arr).unwrap();
+        assert_eq!(&data_arr[..data_len], &send_data_vec[..]);
+    }
+
+    #[test]
+    fn decode_in_place() {
+        let send_data_vec: Vec<u8> = vec![126, 1, 126, 126, 2];
+        let mut send_msg = SerMsg::create_msg_vec(&send_data_vec, 3).unwrap();
+
+        let (id, payload) = decode_frame_in_place(&mut send_msg).unwrap();
+        assert_eq!(id, 3);
+        assert_eq!(payload, &send_data_vec[..]);
+    }
+
+    #[test]
+    fn decode_empty_payload() {
+        let send_msg = SerMsg::create_msg_vec(&[], 1).unwrap();
+
+        let (_id, payload) = decode_frame(&send_msg).unwrap();
+        assert!(payload.is_empty());
+    }
+
+    #[test]
+    fn decode_errors() {
+        let send_msg = SerMsg::create_msg_vec(&[126, 1], 1).unwrap();
+
+        let mut wrong_start = send_msg.clone();
+        wrong_start[0] = 0;
+        assert_eq!(
+            decode_frame(&wrong_start),
+            Err(Error::StartByte { found: 0 })
+        );
+
+        assert_eq!(
+            decode_frame(&send_msg[..send_msg.len() - 1]),
+            Err(Error::FrameLength { len: 7 })
+        );
+
+        let mut high_payload = send_msg.clone();
+        high_payload[3] = 255;
+        assert_eq!(
+            decode_frame(&high_payload),
+            Err(Error::HighPayload { len: 255 })
+        );
+
+        let mut wrong_crc = send_msg.clone();
+        wrong_crc[6] = 0;
+        assert!(matches!(
+            decode_frame(&wrong_crc),
+            Err(Error::Crc { received: 0, .. })
+        ));
+
+        let mut wrong_stop = send_msg.clone();
+        wrong_stop[7] = 0;
+        assert_eq!(decode_frame(&wrong_stop), Err(Error::StopByte { found: 0 }));
+
+        let mut wrong_cobs = send_msg.clone();
+        wrong_cobs[2] = 1;
+        assert_eq!(decode_frame(&wrong_cobs), Err(Error::Cobs));
+        assert_eq!(
+            decode_frame_in_place(&mut wrong_cobs).map(|(id, _)| id),
+            Err(Error::Cobs)
+        );
+    }
+}
+
+#[cfg(test)]
+mod result_tests {
+    use super::*;
+
+    #[test]
+    fn encode_too_large() {
+        let send_data_vec: Vec<u8> = vec![0; 255];
+        assert_eq!(
+            SerMsg::encode_vec(&send_data_vec, 1),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+        assert_eq!(
+            SerMsg::encode_arr(&send_data_vec, 1).map(|(_, msg_len)| msg_len),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+    }
+
+    #[test]
+    fn parse_frame() {
+        let send_data_vec: Vec<u8> = vec![1, 126, 3];
+        let send_msg = SerMsg::encode_vec(&send_data_vec, 2).unwrap();
+
+        let mut ser_msg = SerMsg::new();
+        let mut bytes = &send_msg[..4];
+        assert_eq!(ser_msg.parse(&mut bytes), Ok(None));
+        assert!(bytes.is_empty());
+
+        let mut bytes = &send_msg[4..];
+        let frame = ser_msg.parse(&mut bytes).unwrap().unwrap();
+        assert_eq!(frame.id, 2);
+        assert_eq!(frame.payload, &send_data_vec[..]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let send_msg = SerMsg::encode_vec(&[1], 1).unwrap();
+        let mut ser_msg = SerMsg::new();
+
+        let mut wrong_crc = send_msg.clone();
+        wrong_crc[5] = 0;
+        let mut bytes = &wrong_crc[..];
+        assert_eq!(
+            ser_msg.parse(&mut bytes),
+            Err(Error::Crc {
+                expected: send_msg[5].into(),
+                received: 0
+            })
+        );
+        assert_eq!(bytes, &wrong_crc[6..]);
+
+        let mut wrong_stop = send_msg.clone();
+        wrong_stop[6] = 0;
+        let mut bytes = &wrong_stop[..];
+        assert_eq!(ser_msg.parse(&mut bytes), Err(Error::StopByte { found: 0 }));
+
+        let mut high_payload = send_msg.clone();
+        high_payload[3] = 255;
+        let mut bytes = &high_payload[..];
+        assert_eq!(
+            ser_msg.parse(&mut bytes),
+            Err(Error::HighPayload { len: 255 })
+        );
+    }
+
+    #[test]
+    fn stale_error() {
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        let clock = TickClock::new();
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_timeout(Some(10));
+
+        let mut bytes = &send_msg[..5];
+        assert_eq!(ser_msg.parse_timed(&mut bytes, &clock), Ok(None));
+
+        clock.advance(10);
+        let mut bytes = &send_msg[5..];
+        assert_eq!(ser_msg.parse_timed(&mut bytes, &clock), Err(Error::Stale));
+    }
+
+    #[test]
+    fn error_display() {
+        let e = Error::Crc {
+            expected: 0x9b,
+            received: 0,
+        };
+        assert_eq!(e.to_string(), "CRC mismatch, expected 0x9b, received 0x00");
+        let _: &dyn std::error::Error = &e;
+    }
+}
+
+#[cfg(test)]
+mod encode_into_tests {
+    use super::*;
+
+    #[test]
+    fn encode_into_buffer() {
+        let send_data_vec: Vec<u8> = vec![1, 126, 3];
+        let send_msg = SerMsg::create_msg_vec(&send_data_vec, 1).unwrap();
+
+        let mut tx_buffer = [0xAA; 16];
+        let msg_len = SerMsg::encode_into(&send_data_vec, 1, &mut tx_buffer).unwrap();
+        assert_eq!(msg_len, SerMsg::encoded_len(send_data_vec.len()));
+        assert_eq!(&tx_buffer[..msg_len], &send_msg[..]);
+        assert_eq!(&tx_buffer[msg_len..], &[0xAA; 7]);
+    }
+
+    #[test]
+    fn encode_into_exact_buffer() {
+        let mut tx_buffer = [0; SerMsg::encoded_len(254)];
+        let msg_len = SerMsg::encode_into(&[0; 254], 1, &mut tx_buffer).unwrap();
+        assert_eq!(msg_len, 260);
+    }
+
+    #[test]
+    fn encode_into_small_buffer() {
+        let mut tx_buffer = [0; 8];
+        assert_eq!(
+            SerMsg::encode_into(&[1, 2, 3], 1, &mut tx_buffer),
+            Err(Error::BufferTooSmall { len: 8, needed: 9 })
+        );
+        assert_eq!(
+            SerMsg::encode_into(&[0; 255], 1, &mut tx_buffer),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+    }
+
+    #[test]
+    fn encode_extend_vec() {
+        let mut send_bytes: Vec<u8> = Vec::with_capacity(64);
+        SerMsg::encode_extend(&[1], 1, &mut send_bytes).unwrap();
+        SerMsg::encode_extend(&[2, 3], 2, &mut send_bytes).unwrap();
+        assert_eq!(send_bytes.capacity(), 64);
+
+        let mut expected = SerMsg::create_msg_vec(&[1], 1).unwrap();
+        expected.extend(SerMsg::create_msg_vec(&[2, 3], 2).unwrap());
+        assert_eq!(send_bytes, expected);
+
+        assert_eq!(
+            SerMsg::encode_extend(&[0; 255], 1, &mut send_bytes),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+        assert_eq!(send_bytes, expected);
+    }
+}
+
+#[cfg(test)]
+mod scatter_gather_tests {
+    use super::*;
+
+    #[test]
+    fn slices_match_single_slice() {
+        let header: Vec<u8> = vec![126, 1, 2];
+        let samples: Vec<u8> = vec![3, 126, 126, 4, 126];
+        let empty: Vec<u8> = vec![];
+        let parts: [&[u8]; 3] = [&header, &empty, &samples];
+        let data_vec: Vec<u8> = parts.concat();
+        let send_msg = SerMsg::create_msg_vec(&data_vec, 7).unwrap();
+
+        assert_eq!(SerMsg::encode_slices_vec(&parts, 7).unwrap(), send_msg);
+
+        let mut tx_buffer = [0; 32];
+        let msg_len = SerMsg::encode_slices_into(&parts, 7, &mut tx_buffer).unwrap();
+        assert_eq!(&tx_buffer[..msg_len], &send_msg[..]);
+
+        let msg_iter = SerMsg::encode_slices_iter(&parts, 7).unwrap();
+        assert_eq!(msg_iter.len(), send_msg.len());
+        assert_eq!(msg_iter.collect::<Vec<u8>>(), send_msg);
+    }
+
+    #[test]
+    fn iter_without_cobs() {
+        let send_msg = SerMsg::create_msg_vec(&[1, 2, 3], 1).unwrap();
+        let msg_iter = SerMsg::encode_slices_iter(&[&[1], &[2, 3]], 1).unwrap();
+        assert_eq!(msg_iter.collect::<Vec<u8>>(), send_msg);
+    }
+
+    #[test]
+    fn iter_empty_payload() {
+        let send_msg = SerMsg::create_msg_vec(&[], 1).unwrap();
+        let msg_iter = SerMsg::encode_slices_iter(&[], 1).unwrap();
+        assert_eq!(msg_iter.collect::<Vec<u8>>(), send_msg);
+    }
+
+    #[test]
+    fn parse_slices_msg() {
+        let parts: [&[u8]; 2] = [&[126; 100], &[126; 154]];
+        let send_msg = SerMsg::encode_slices_vec(&parts, 1).unwrap();
+
+        let mut ser_msg = SerMsg::new();
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+
+        match state {
+            ParseState::DataReady => {
+                assert_eq!(&[126; 254], ser_msg.return_read_data());
+            }
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn slices_too_large() {
+        let parts: [&[u8]; 2] = [&[0; 200], &[0; 55]];
+        assert_eq!(
+            SerMsg::encode_slices_vec(&parts, 1),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+        assert!(SerMsg::encode_slices_iter(&parts, 1).is_err());
+    }
+}
+
+#[cfg(test)]
+mod builder_tests {
+    use super::*;
+
+    #[test]
+    fn build_msg() {
+        let send_data_vec: Vec<u8> = vec![1, 126, 2, 126];
+        let mut builder = FrameBuilder::new();
+        let next_ind = builder.put_bytes(&send_data_vec[..2]).unwrap();
+        assert_eq!(next_ind, 2);
+        builder.put_bytes(&send_data_vec[2..]).unwrap();
+        assert_eq!(builder.payload(), &send_data_vec[..]);
+
+        let send_msg = builder.finish(3);
+        assert_eq!(
+            send_msg,
+            &SerMsg::create_msg_vec(&send_data_vec, 3).unwrap()[..]
+        );
+        assert!(builder.is_empty());
+    }
+
+    #[test]
+    fn build_with_payload_mut() {
+        let mut builder = FrameBuilder::new();
+        assert_eq!(builder.payload_mut().len(), 254);
+        builder.payload_mut()[..3].copy_from_slice(&[1, 2, 3]);
+        builder.set_len(3).unwrap();
+
+        let send_msg = builder.finish(1);
+        assert_eq!(
+            send_msg,
+            &SerMsg::create_msg_vec(&[1, 2, 3], 1).unwrap()[..]
+        );
+        assert_eq!(
+            builder.set_len(255),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+    }
+
+    #[test]
+    fn put_at_index() {
+        let mut builder = FrameBuilder::new();
+        assert_eq!(builder.put_bytes_at(2, &[3, 4]), Ok(4));
+        assert_eq!(builder.put_bytes_at(0, &[1, 2]), Ok(2));
+        assert_eq!(builder.len(), 4);
+        assert_eq!(builder.payload(), &[1, 2, 3, 4]);
+
+        assert_eq!(
+            builder.put_bytes_at(250, &[0; 5]),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+        assert_eq!(
+            builder.put_bytes_at(usize::MAX, &[1, 2]),
+            Err(Error::PayloadTooLarge { len: usize::MAX })
+        );
+        assert_eq!(builder.len(), 4);
+    }
+
+    #[test]
+    fn build_empty_msg() {
+        let mut builder = FrameBuilder::new();
+        let send_msg = builder.finish(1);
+        assert_eq!(send_msg, &SerMsg::create_msg_vec(&[], 1).unwrap()[..]);
+    }
+
+    #[cfg(feature = "zerocopy")]
+    #[test]
+    fn put_objects() {
+        use zerocopy::AsBytes;
+
+        #[repr(C, packed)]
+        #[derive(AsBytes, Clone, Copy)]
+        struct Header {
+            kind: u8,
+            count: u16,
+        }
+
+        let header = Header {
+            kind: 126,
+            count: 300,
+        };
+        let samples: [i16; 2] = [-1, 126];
+        let mut builder = FrameBuilder::new();
+        let next_ind = builder.put(&header).unwrap();
+        assert_eq!(builder.put_at(next_ind, &samples), Ok(7));
+
+        let send_msg = builder.finish(1).to_vec();
+        let expected =
+            SerMsg::encode_slices_vec(&[header.as_bytes(), samples.as_bytes()], 1).unwrap();
+        assert_eq!(send_msg, expected);
+    }
+}
+
+#[cfg(test)]
+mod reader_tests {
+    use super::*;
+
+    #[test]
+    fn read_bytes() {
+        let send_msg = SerMsg::create_msg_vec(&[1, 126, 3, 4], 1).unwrap();
+        let mut ser_msg = SerMsg::new();
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+
+        match state {
+            ParseState::DataReady => {
+                let mut reader = ser_msg.payload_reader();
+                assert_eq!(reader.remaining(), 4);
+                assert_eq!(reader.get_bytes(2), Ok(&[1, 126][..]));
+                assert_eq!(reader.position(), 2);
+                assert_eq!(reader.get_bytes(2), Ok(&[3, 4][..]));
+                assert_eq!(reader.remaining(), 0);
+                assert_eq!(reader.get_bytes(0), Ok(&[][..]));
+            }
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn read_out_of_bounds() {
+        let mut reader = PayloadReader::new(&[1, 2, 3]);
+        assert_eq!(
+            reader.get_bytes_at(2, 2),
+            Err(Error::OutOfBounds {
+                index: 2,
+                len: 2,
+                payload_len: 3
+            })
+        );
+        assert_eq!(reader.position(), 0);
+        assert!(reader.get_bytes_at(usize::MAX, 2).is_err());
+
+        reader.set_position(5);
+        assert_eq!(reader.remaining(), 0);
+        assert!(reader.get_bytes(1).is_err());
+    }
+
+    #[test]
+    fn read_frame_ref() {
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        let mut ser_msg = SerMsg::new();
+        let mut bytes = &send_msg[..];
+        let frame = ser_msg.parse(&mut bytes).unwrap().unwrap();
+
+        let mut reader = frame.reader();
+        assert_eq!(reader.get_bytes_at(1, 2), Ok(&[2, 3][..]));
+    }
+
+    #[cfg(feature = "zerocopy")]
+    #[test]
+    fn read_objects() {
+        use zerocopy::{AsBytes, FromBytes};
+
+        #[repr(C, packed)]
+        #[derive(AsBytes, FromBytes, Clone, Copy, Debug, PartialEq)]
+        struct Header {
+            kind: u8,
+            count: u16,
+        }
+
+        let header = Header {
+            kind: 126,
+            count: 300,
+        };
+        let samples: [i16; 2] = [-1, 126];
+        let send_msg =
+            SerMsg::encode_slices_vec(&[header.as_bytes(), samples.as_bytes()], 1).unwrap();
+
+        let mut ser_msg = SerMsg::new();
+        let mut bytes = &send_msg[..];
+        let frame = ser_msg.parse(&mut bytes).unwrap().unwrap();
+        let mut reader = frame.reader();
+
+        assert_eq!(reader.get_at::<[i16; 2]>(3), Ok(samples));
+        assert_eq!(reader.get_at::<Header>(0), Ok(header));
+        assert_eq!(reader.get::<i16>(), Ok(-1));
+        assert_eq!(reader.get::<i16>(), Ok(126));
+        assert_eq!(
+            reader.get::<u8>(),
+            Err(Error::OutOfBounds {
+                index: 7,
+                len: 1,
+                payload_len: 7
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    const CONFIG: FramingConfig = FramingConfig::new()
+        .with_start_byte(0x02)
+        .with_stop_byte(0x03)
+        .with_max_payload(128);
+
+    #[test]
+    fn default_config() {
+        let config = FramingConfig::default();
+        assert_eq!(config, FramingConfig::DEFAULT);
+        assert_eq!(config.start_byte(), 126);
+        assert_eq!(config.stop_byte(), 129);
+        assert_eq!(config.max_payload(), 254);
+        assert_eq!(
+            config.encode_vec(&[1, 126, 3], 1),
+            SerMsg::encode_vec(&[1, 126, 3], 1)
+        );
+        assert_eq!(SerMsg::new().config(), config);
+    }
+
+    #[test]
+    fn custom_config() {
+        let send_data = [1, 2, 126, 3, 2, 129];
+        let send_msg = CONFIG.encode_vec(&send_data, 1).unwrap();
+        assert_eq!(send_msg[0], 0x02);
+        assert_eq!(send_msg[2], 1);
+        assert_eq!(*send_msg.last().unwrap(), 0x03);
+
+        let mut ser_msg = SerMsg::with_config(CONFIG);
+        let (state, parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+        assert_eq!(parsed_bytes, send_msg.len());
+        match state {
+            ParseState::DataReady => {
+                assert_eq!(ser_msg.return_read_data(), &send_data);
+                assert_eq!(ser_msg.return_msg_id(), 1);
+            }
+            _ => {
+                panic!()
+            }
+        }
+
+        let mut ser_msg = SerMsg::new();
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+        match state {
+            ParseState::Continue => (),
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn custom_max_payload() {
+        assert!(CONFIG.encode_vec(&[0; 128], 1).is_ok());
+        assert_eq!(
+            CONFIG.encode_vec(&[0; 129], 1),
+            Err(Error::PayloadTooLarge { len: 129 })
+        );
+
+        let send_msg = SerMsg::encode_vec(&[0; 129], 1).unwrap();
+        let mut ser_msg = SerMsg::with_config(FramingConfig::new().with_max_payload(128));
+        let (state, parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+        assert_eq!(parsed_bytes, 4);
+        match state {
+            ParseState::HighPayloadError => (),
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn max_payload_too_large() {
+        let _config = FramingConfig::new().with_max_payload(255);
+    }
+
+    #[test]
+    fn set_config() {
+        let send_msg = CONFIG.encode_vec(&[1, 2, 3], 1).unwrap();
+        let mut ser_msg = SerMsg::new();
+        ser_msg.parse_read_bytes(&[126, 1, 0xFF]);
+        ser_msg.set_config(CONFIG);
+        assert_eq!(ser_msg.config(), CONFIG);
+
+        let mut bytes = &send_msg[..];
+        let frame = ser_msg.parse(&mut bytes).unwrap().unwrap();
+        assert_eq!(frame.payload, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn custom_config_encoders() {
+        let send_msg = CONFIG.encode_vec(&[2, 126, 2], 1).unwrap();
+
+        let mut tx_buffer = [0; 64];
+        let msg_len = CONFIG
+            .encode_slices_into(&[&[2], &[126, 2]], 1, &mut tx_buffer)
+            .unwrap();
+        assert_eq!(&tx_buffer[..msg_len], &send_msg[..]);
+
+        let iter_msg: Vec<u8> = CONFIG
+            .encode_slices_iter(&[&[2, 126], &[2]], 1)
+            .unwrap()
+            .collect();
+        assert_eq!(iter_msg, send_msg);
+
+        let mut builder = FrameBuilder::with_config(CONFIG);
+        assert_eq!(builder.payload_mut().len(), 128);
+        assert!(builder.put_bytes(&[0; 129]).is_err());
+        builder.put_bytes(&[2, 126, 2]).unwrap();
+        assert_eq!(builder.finish(1), &send_msg[..]);
+    }
+
+    #[test]
+    fn custom_config_decoders() {
+        let mut send_msg = CONFIG.encode_vec(&[2, 126, 2], 1).unwrap();
+
+        assert_eq!(
+            decode_frame(&send_msg),
+            Err(Error::StartByte { found: 0x02 })
+        );
+        let (id, payload) = CONFIG.decode_frame(&send_msg).unwrap();
+        assert_eq!(id, 1);
+        assert_eq!(payload.to_vec(), vec![2, 126, 2]);
+
+        let (id, payload) = CONFIG.decode_frame_in_place(&mut send_msg).unwrap();
+        assert_eq!(id, 1);
+        assert_eq!(payload, &[2, 126, 2]);
+    }
+}
+
+#[cfg(test)]
+mod crc_tests {
+    use super::*;
+
+    fn parse_frames<C: Crc>(
+        ser_msg: &mut SerMsg<254, C>,
+        rcvd_bytes: &[u8],
+    ) -> Vec<Result<Message, Error>> {
+        let mut frames = ser_msg.frames(rcvd_bytes);
+        let mut msgs = Vec::new();
+        while let Some(frame) = frames.next_frame() {
+            msgs.push(frame.map(|frame| Message::try_from(frame).unwrap()));
+        }
+        msgs
+    }
+
+    #[test]
+    fn check_values() {
+        static CRC8_SMBUS: [u8; 256] = crc8_table(0x07);
+        assert_eq!(Crc8::with_table(&CRC8_SMBUS).checksum(b"123456789"), 0xF4);
+        assert_eq!(Crc8::new().checksum(b"123456789"), 0xEA);
+        assert_eq!(Crc16::new().checksum(b"123456789"), 0x31C3);
+        assert_eq!(Crc32::new().checksum(b"123456789"), 0x89A1_897F);
+    }
+
+    #[test]
+    fn incremental_update() {
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), 0x89A1_897F);
+        crc.reset();
+        assert_eq!(crc.finish(), 0);
+    }
+
+    #[test]
+    fn crc16_messages() {
+        let config = FramingConfig::DEFAULT;
+        let send_msg = config
+            .encode_slices_vec_crc(&mut Crc16::new(), &[&[1, 126, 3]], 1)
+            .unwrap();
+        assert_eq!(send_msg.len(), SerMsg::encoded_len_crc::<Crc16>(3));
+        let crc = Crc16::new().checksum(&send_msg[4..7]);
+        assert_eq!(&send_msg[7..9], &(crc as u16).to_be_bytes());
+
+        let mut ser_msg = SerMsg::with_crc(config, Crc16::new());
+        let (state, parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+        assert_eq!(parsed_bytes, send_msg.len());
+        match state {
+            ParseState::DataReady => {
+                assert_eq!(ser_msg.return_read_data(), &[1, 126, 3]);
+            }
+            _ => {
+                panic!()
+            }
+        }
+
+        let mut ser_msg = SerMsg::new();
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+        match state {
+            ParseState::CrcError => (),
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn crc32_encoders() {
+        let config = FramingConfig::DEFAULT;
+        let send_msg = config
+            .encode_slices_vec_crc(&mut Crc32::new(), &[&[1, 126], &[126, 4]], 1)
+            .unwrap();
+
+        let iter_msg: Vec<u8> = config
+            .encode_slices_iter_crc(Crc32::new(), &[&[1], &[126, 126, 4]], 1)
+            .unwrap()
+            .collect();
+        assert_eq!(iter_msg, send_msg);
+        let iter = config
+            .encode_slices_iter_crc(Crc32::new(), &[&[1, 126, 126, 4]], 1)
+            .unwrap();
+        assert_eq!(iter.len(), send_msg.len());
+
+        let mut builder = FrameBuilder::with_crc(config, Crc32::new());
+        builder.put_bytes(&[1, 126, 126, 4]).unwrap();
+        assert_eq!(builder.finish(1), &send_msg[..]);
+
+        let mut tx_buffer = [0; 16];
+        assert_eq!(
+            config.encode_slices_into_crc(
+                &mut Crc32::new(),
+                &[&[1, 126, 126, 4]],
+                1,
+                &mut tx_buffer[..12]
+            ),
+            Err(Error::BufferTooSmall {
+                len: 12,
+                needed: 13
+            })
+        );
+    }
+
+    #[test]
+    fn crc32_decoders() {
+        let config = FramingConfig::DEFAULT;
+        let mut send_msg = config
+            .encode_slices_vec_crc(&mut Crc32::new(), &[&[1, 126, 3]], 2)
+            .unwrap();
+
+        assert_eq!(decode_frame(&send_msg), Err(Error::FrameLength { len: 12 }));
+        let (id, payload) = config
+            .decode_frame_crc(&mut Crc32::new(), &send_msg)
+            .unwrap();
+        assert_eq!(id, 2);
+        assert_eq!(payload.to_vec(), vec![1, 126, 3]);
+
+        let (id, payload) = config
+            .decode_frame_in_place_crc(&mut Crc32::new(), &mut send_msg)
+            .unwrap();
+        assert_eq!(id, 2);
+        assert_eq!(payload, &[1, 126, 3]);
+
+        let mut wrong_crc = config
+            .encode_slices_vec_crc(&mut Crc32::new(), &[&[1, 126, 3]], 2)
+            .unwrap();
+        wrong_crc[7] ^= 0xFF;
+        assert!(matches!(
+            config.decode_frame_crc(&mut Crc32::new(), &wrong_crc),
+            Err(Error::Crc { .. })
+        ));
+    }
+
+    #[test]
+    fn crc16_errors() {
+        let config = FramingConfig::DEFAULT;
+        let send_msg = config
+            .encode_slices_vec_crc(&mut Crc16::new(), &[&[1, 2]], 1)
+            .unwrap();
+        let expected = Crc16::new().checksum(&[1, 2]);
+
+        let mut wrong_crc = send_msg.clone();
+        wrong_crc[7] ^= 0xFF;
+        let mut ser_msg = SerMsg::with_crc(config, Crc16::new());
+        assert_eq!(
+            parse_frames(&mut ser_msg, &wrong_crc),
+            vec![Err(Error::Crc {
+                expected,
+                received: expected ^ 0xFF
+            })]
+        );
+
+        let mut wrong_stop = send_msg.clone();
+        wrong_stop[8] = 0;
+        assert_eq!(
+            parse_frames(&mut ser_msg, &wrong_stop),
+            vec![Err(Error::StopByte { found: 0 })]
+        );
+    }
+
+    #[test]
+    fn crc16_resync() {
+        let config = FramingConfig::DEFAULT;
+        let send_msg = config
+            .encode_slices_vec_crc(&mut Crc16::new(), &[&[1, 2, 3]], 1)
+            .unwrap();
+        // The header of an empty message cut off by line noise
+        let mut rcvd_bytes = vec![126, 5, 0xFF, 0];
+        rcvd_bytes.extend(&send_msg);
+
+        let mut ser_msg = SerMsg::with_crc(config, Crc16::new());
+        ser_msg.set_resync(true);
+        let frames = parse_frames(&mut ser_msg, &rcvd_bytes);
+        assert!(matches!(frames[0], Err(Error::Crc { .. })));
+        assert_eq!(
+            frames.last(),
+            Some(&Ok(Message::new(1, &[1, 2, 3]).unwrap()))
+        );
+    }
+}
+
+#[cfg(test)]
+mod capacity_tests {
+    use super::*;
+
+    #[test]
+    fn small_capacity() {
+        let mut ser_msg: SerMsg<8> = SerMsg::default();
+        assert!(core::mem::size_of::<SerMsg<8>>() < core::mem::size_of::<SerMsg>() / 4);
+
+        let send_msg = SerMsg::encode_vec(&[126; 8], 1).unwrap();
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+        match state {
+            ParseState::DataReady => {
+                assert_eq!(ser_msg.return_read_data(), &[126; 8]);
+            }
+            _ => {
+                panic!()
+            }
+        }
+
+        let send_msg = SerMsg::encode_vec(&[1; 9], 1).unwrap();
+        let (state, parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+        assert_eq!(parsed_bytes, 4);
+        match state {
+            ParseState::HighPayloadError => (),
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn small_capacity_crc16() {
+        let config = FramingConfig::DEFAULT;
+        let send_msg = config
+            .encode_slices_vec_crc(&mut Crc16::new(), &[&[1, 2, 3, 4]], 1)
+            .unwrap();
+        let mut ser_msg = SerMsg::<4, Crc16>::with_capacity(config, Crc16::new());
+        let mut bytes = &send_msg[..];
+        let frame = ser_msg.parse(&mut bytes).unwrap().unwrap();
+        assert_eq!(frame.payload, &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn small_capacity_resync() {
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3, 4], 1).unwrap();
+        // Line noise looking like the header of a message with 4 bytes
+        let mut rcvd_bytes = vec![126, 5, 0xFF, 4, 6];
+        rcvd_bytes.extend(&send_msg);
+
+        let mut ser_msg: SerMsg<4> = SerMsg::default();
+        ser_msg.set_resync(true);
+        let mut rcvd_data = Vec::new();
+        ser_msg.for_each_frame(&rcvd_bytes, |frame| {
+            if let Ok(frame) = frame {
+                rcvd_data.push(frame.payload.to_vec());
+            }
+        });
+        assert_eq!(rcvd_data, vec![vec![1, 2, 3, 4]]);
+    }
+
+    #[test]
+    fn borrowed_buffer() {
+        let mut rx_buffer = [0; 4];
+        let mut ser_msg = SerMsgRef::new(&mut rx_buffer);
+
+        let send_msg = SerMsg::encode_vec(&[1, 126, 3, 4], 1).unwrap();
+        let mut bytes = &send_msg[..];
+        let frame = ser_msg.parse(&mut bytes).unwrap().unwrap();
+        assert_eq!(frame.payload, &[1, 126, 3, 4]);
+
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3, 4, 5], 1).unwrap();
+        let mut bytes = &send_msg[..];
+        assert_eq!(
+            ser_msg.parse(&mut bytes),
+            Err(Error::HighPayload { len: 5 })
+        );
+    }
+
+    #[test]
+    fn borrowed_buffer_resync() {
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3, 4, 5, 6], 1).unwrap();
+        let mut rcvd_bytes = vec![126, 5, 0xFF, 6, 7];
+        rcvd_bytes.extend(&send_msg);
+
+        let mut rx_buffer = [0; 6];
+        let mut resync_buffer = [0; 6];
+        let mut ser_msg = SerMsgRef::with_buffers(
+            FramingConfig::DEFAULT,
+            Crc8::new(),
+            &mut rx_buffer[..],
+            &mut resync_buffer[..],
+        );
+        ser_msg.set_resync(true);
+        let mut rcvd_data = Vec::new();
+        ser_msg.for_each_frame(&rcvd_bytes, |frame| {
+            if let Ok(frame) = frame {
+                rcvd_data.push(frame.payload.to_vec());
+            }
+        });
+        assert_eq!(rcvd_data, vec![vec![1, 2, 3, 4, 5, 6]]);
+
+        // Without a resync buffer the failed message doesn't fit and is dropped
+        let mut rx_buffer = [0; 6];
+        let mut ser_msg = SerMsgRef::new(&mut rx_buffer);
+        ser_msg.set_resync(true);
+        let mut rcvd_data = Vec::new();
+        ser_msg.for_each_frame(&rcvd_bytes, |frame| {
+            if let Ok(frame) = frame {
+                rcvd_data.push(frame.payload.to_vec());
+            }
+        });
+        assert!(rcvd_data.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod single_pass_tests {
+    use super::*;
+
+    fn raw_msg(cobs_byte: u8, payload: &[u8]) -> Vec<u8> {
+        let mut msg = vec![126, 1, cobs_byte, payload.len() as u8];
+        msg.extend(payload);
+        msg.push(Crc8::new().checksum(payload) as u8);
+        msg.push(129);
+        msg
+    }
+
+    #[test]
+    fn short_msg_after_long_msg() {
+        let mut rcvd_bytes = SerMsg::encode_vec(&[126; 254], 1).unwrap();
+        rcvd_bytes.extend(SerMsg::encode_vec(&[1, 2], 2).unwrap());
+
+        let mut ser_msg = SerMsg::new();
+        let mut frames = ser_msg.frames(&rcvd_bytes);
+        assert_eq!(
+            frames.next_frame().unwrap().unwrap().payload,
+            &[126; 254][..]
+        );
+        assert_eq!(frames.next_frame().unwrap().unwrap().payload, &[1, 2]);
+        assert!(frames.next_frame().is_none());
+    }
+
+    #[test]
+    fn start_byte_in_payload() {
+        // Not created by an encoder, but the start byte is kept like before
+        let mut ser_msg = SerMsg::new();
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&raw_msg(0, &[2, 126, 0]));
+        match state {
+            ParseState::DataReady => {
+                assert_eq!(&[126, 126, 126], ser_msg.return_read_data());
+            }
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn cobs_outside_payload() {
+        let mut ser_msg = SerMsg::new();
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&raw_msg(0, &[3, 1, 2]));
+        match state {
+            ParseState::COBSError => {
+                // Success
+            }
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn resync_unpacked_payload() {
+        // The failed message is parsed again as it was received, not with the unpacked start byte
+        let send_data_vec: Vec<u8> = vec![5, 126, 6];
+        let mut rcvd_bytes = SerMsg::encode_vec(&send_data_vec, 1).unwrap();
+        rcvd_bytes[8] = 126;
+        rcvd_bytes.extend(SerMsg::encode_vec(&send_data_vec, 2).unwrap()[1..].to_vec());
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_resync(true);
+        let mut rcvd_data = Vec::new();
+        ser_msg.for_each_frame(&rcvd_bytes, |frame| {
+            if let Ok(frame) = frame {
+                rcvd_data.push((frame.id, frame.payload.to_vec()));
+            }
+        });
+        assert_eq!(rcvd_data, vec![(2, send_data_vec)]);
+    }
+}
+
+#[cfg(test)]
+mod queue_tests {
+    use super::*;
+
+    fn three_msgs() -> Vec<u8> {
+        let mut rcvd_bytes = Vec::new();
+        for id in 1..4 {
+            SerMsg::encode_extend(&[id, 126, id], id, &mut rcvd_bytes).unwrap();
+        }
+        rcvd_bytes
+    }
+
+    #[test]
+    fn queue_all_msgs() {
+        let rcvd_bytes = three_msgs();
+        let mut queued_msg = QueuedSerMsg::new(FrameRing::<4>::new(), Overflow::DropOldest);
+        let mut bytes = &rcvd_bytes[..];
+        assert_eq!(queued_msg.parse(&mut bytes), Ok(()));
+        assert!(bytes.is_empty());
+        assert_eq!(queued_msg.len(), 3);
+        assert_eq!(queued_msg.front().unwrap().payload, &[1, 126, 1]);
+        for id in 1..4 {
+            let frame = queued_msg.pop().unwrap();
+            assert_eq!(frame.id(), id);
+            assert_eq!(frame.payload(), &[id, 126, id]);
+        }
+        assert!(queued_msg.pop().is_none());
+        assert!(queued_msg.is_empty());
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let rcvd_bytes = three_msgs();
+        let mut queued_msg = QueuedSerMsg::new(FrameRing::<2>::new(), Overflow::DropOldest);
+        let mut bytes = &rcvd_bytes[..];
+        assert_eq!(queued_msg.parse(&mut bytes), Ok(()));
+        assert_eq!(queued_msg.pop().unwrap().id(), 2);
+        assert_eq!(queued_msg.pop().unwrap().id(), 3);
+        assert_eq!(
+            queued_msg.counters(),
+            QueueCounters {
+                received: 3,
+                dropped: 1,
+                errors: 0
+            }
+        );
+    }
+
+    #[test]
+    fn drop_newest() {
+        let rcvd_bytes = three_msgs();
+        let mut queued_msg = QueuedSerMsg::new(FrameRing::<2>::new(), Overflow::DropNewest);
+        let mut bytes = &rcvd_bytes[..];
+        assert_eq!(queued_msg.parse(&mut bytes), Ok(()));
+        assert!(queued_msg.is_full());
+        assert_eq!(queued_msg.pop().unwrap().id(), 1);
+        assert_eq!(queued_msg.pop().unwrap().id(), 2);
+        assert_eq!(queued_msg.counters().dropped, 1);
+    }
+
+    #[test]
+    fn queue_full_error() {
+        let rcvd_bytes = three_msgs();
+        let mut queued_msg = QueuedSerMsg::new(FrameRing::<2>::new(), Overflow::Error);
+        let mut bytes = &rcvd_bytes[..];
+        assert_eq!(queued_msg.parse(&mut bytes), Err(Error::QueueFull));
+        // The last message stays unparsed until there is room in the queue
+        assert_eq!(bytes, &rcvd_bytes[2 * SerMsg::encoded_len(3)..]);
+        assert_eq!(queued_msg.pop().unwrap().id(), 1);
+        assert_eq!(queued_msg.parse(&mut bytes), Ok(()));
+        assert_eq!(queued_msg.pop().unwrap().id(), 2);
+        assert_eq!(queued_msg.pop().unwrap().id(), 3);
+        assert_eq!(queued_msg.counters().dropped, 0);
+    }
+
+    #[test]
+    fn count_errors() {
+        let mut rcvd_bytes = three_msgs();
+        // Corrupts the CRC of the first message
+        rcvd_bytes[7] ^= 1;
+        let mut queued_msg = QueuedSerMsg::new(FrameRing::<4>::new(), Overflow::DropOldest);
+        let mut bytes = &rcvd_bytes[..];
+        assert_eq!(queued_msg.parse(&mut bytes), Ok(()));
+        assert_eq!(queued_msg.len(), 2);
+        assert_eq!(queued_msg.counters().errors, 1);
+        queued_msg.reset_counters();
+        assert_eq!(queued_msg.counters(), QueueCounters::default());
+    }
+
+    #[test]
+    fn queue_with_parser() {
+        let mut ser_msg = SerMsg::<4, Crc16>::with_capacity(FramingConfig::DEFAULT, Crc16::new());
+        ser_msg.set_resync(true);
+        let mut rcvd_bytes = vec![126];
+        for id in 1..4 {
+            FramingConfig::DEFAULT
+                .encode_slices_extend_crc(&mut Crc16::new(), &[&[id, 126, id]], id, &mut rcvd_bytes)
+                .unwrap();
+        }
+
+        let mut queued_msg =
+            QueuedSerMsg::with_parser(ser_msg, FrameDeque::new(2), Overflow::DropNewest);
+        let mut bytes = &rcvd_bytes[..];
+        assert_eq!(queued_msg.parse(&mut bytes), Ok(()));
+        assert_eq!(
+            queued_msg.pop(),
+            Some(Message::new(1, &[1, 126, 1]).unwrap())
+        );
+        assert_eq!(queued_msg.pop().unwrap().id, 2);
+        assert!(queued_msg.pop().is_none());
+        assert_eq!(queued_msg.counters().dropped, 1);
+    }
+
+    #[test]
+    fn ring_payload_capacity() {
+        let mut rcvd_bytes = SerMsg::encode_vec(&[1; 16], 1).unwrap();
+        SerMsg::encode_extend(&[2; 17], 2, &mut rcvd_bytes).unwrap();
+
+        let ring = FrameRing::<8, 16>::new();
+        assert!(core::mem::size_of_val(&ring) < 8 * 254);
+        let mut queued_msg = QueuedSerMsg::new(ring, Overflow::DropOldest);
+        let mut bytes = &rcvd_bytes[..];
+        assert_eq!(queued_msg.parse(&mut bytes), Ok(()));
+        // The second payload doesn't fit into the ring
+        assert_eq!(queued_msg.len(), 1);
+        assert_eq!(queued_msg.pop().unwrap().payload(), &[1; 16]);
+        assert_eq!(queued_msg.counters().received, 2);
+        assert_eq!(queued_msg.counters().dropped, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "the deque needs room for at least one message")]
+    fn deque_without_room() {
+        FrameDeque::new(0);
+    }
+}
+
+#[cfg(test)]
+mod rx_tests {
+    use super::*;
+    use core::cell::RefCell;
+    use critical_section::Mutex;
+
+    #[test]
+    fn split_push_pop() {
+        let mut ser_msg = SerMsg::new();
+        let mut queue = RxQueue::<2>::new();
+        let (mut producer, mut consumer) = ser_msg.split(&mut queue);
+
+        let send_msg = SerMsg::encode_vec(&[1, 126, 3], 1).unwrap();
+        for val in &send_msg[..send_msg.len() - 1] {
+            assert_eq!(producer.push_byte(*val), Ok(false));
+        }
+        assert!(consumer.pop().is_none());
+        assert_eq!(producer.push_byte(129), Ok(true));
+        assert_eq!(consumer.len(), 1);
+        assert_eq!(consumer.front().unwrap().payload, &[1, 126, 3]);
+        let frame = consumer.pop().unwrap();
+        assert_eq!((frame.id(), frame.payload()), (1, &[1, 126, 3][..]));
+        assert!(consumer.is_empty());
+    }
+
+    #[test]
+    fn full_queue() {
+        let mut rcvd_bytes = Vec::new();
+        for id in 0..3 {
+            SerMsg::encode_extend(&[id], id, &mut rcvd_bytes).unwrap();
+        }
+        let mut ser_msg = SerMsg::new();
+        let mut queue = RxQueue::<2>::new();
+        let (mut producer, mut consumer) = ser_msg.split(&mut queue);
+        producer.push_bytes(&rcvd_bytes[..rcvd_bytes.len() - 1]);
+        assert_eq!(producer.push_byte(129), Err(Error::QueueFull));
+        assert_eq!(
+            producer.counters(),
+            QueueCounters {
+                received: 3,
+                dropped: 1,
+                errors: 0
+            }
+        );
+        assert_eq!(consumer.pop().unwrap().id(), 0);
+        assert_eq!(consumer.pop().unwrap().id(), 1);
+        assert!(consumer.pop().is_none());
+    }
+
+    #[test]
+    fn queue_payload_capacity() {
+        let mut rcvd_bytes = SerMsg::encode_vec(&[1; 16], 1).unwrap();
+        SerMsg::encode_extend(&[2; 17], 2, &mut rcvd_bytes).unwrap();
+
+        let mut ser_msg = SerMsg::new();
+        let mut queue = RxQueue::<4, 16>::new();
+        assert!(core::mem::size_of_val(&queue) < 4 * 254);
+        let (mut producer, mut consumer) = ser_msg.split(&mut queue);
+        producer.push_bytes(&rcvd_bytes[..rcvd_bytes.len() - 1]);
+        assert_eq!(
+            producer.push_byte(129),
+            Err(Error::PayloadTooLarge { len: 17 })
+        );
+        assert_eq!(producer.counters().dropped, 1);
+        assert_eq!(consumer.pop().unwrap().payload(), &[1; 16]);
+        assert!(consumer.pop().is_none());
+    }
+
+    #[test]
+    fn producer_thread() {
+        let mut ser_msg = SerMsg::new();
+        let mut queue = RxQueue::<4>::new();
+        let (mut producer, mut consumer) = ser_msg.split(&mut queue);
+
+        let mut popped = Vec::new();
+        let counters = std::thread::scope(|s| {
+            let producer = s.spawn(move || {
+                for id in 0..200_u8 {
+                    for val in SerMsg::encode_vec(&[id, 126, id], id).unwrap() {
+                        producer.push_byte(val).ok();
+                    }
+                }
+                producer.counters()
+            });
+            while !producer.is_finished() || !consumer.is_empty() {
+                if let Some(frame) = consumer.pop() {
+                    assert_eq!(frame.payload(), &[frame.id(), 126, frame.id()]);
+                    popped.push(frame.id());
+                }
+            }
+            producer.join().unwrap()
+        });
+        assert_eq!(counters.received, 200);
+        assert_eq!(popped.len() as u32, counters.received - counters.dropped);
+        assert!(popped.windows(2).all(|ids| ids[0] < ids[1]));
+    }
+
+    #[test]
+    fn producer_in_critical_section() {
+        type Producer = RxProducer<'static, [u8; 254], Crc8, 2>;
+        static QUEUE_PRODUCER: Mutex<RefCell<Option<Producer>>> = Mutex::new(RefCell::new(None));
+
+        let ser_msg: &'static mut SerMsg = Box::leak(Box::new(SerMsg::new()));
+        let queue: &'static mut RxQueue<2> = Box::leak(Box::new(RxQueue::new()));
+        let (producer, mut consumer) = ser_msg.split(queue);
+        critical_section::with(|cs| QUEUE_PRODUCER.borrow(cs).replace(Some(producer)));
+
+        let interrupt = std::thread::spawn(|| {
+            for val in SerMsg::encode_vec(&[1, 2, 3], 1).unwrap() {
+                critical_section::with(|cs| {
+                    let mut producer = QUEUE_PRODUCER.borrow_ref_mut(cs);
+                    producer.as_mut().unwrap().push_byte(val).unwrap();
+                });
+            }
+        });
+        interrupt.join().unwrap();
+        assert_eq!(consumer.pop().unwrap().payload(), &[1, 2, 3]);
+    }
+}
+
+#[cfg(test)]
+mod tx_tests {
+    use super::*;
+
+    fn drain<const N: usize, C: Crc>(tx_queue: &mut TxQueue<N, C>) -> Vec<u8> {
+        let mut sent_bytes = Vec::new();
+        while let Some(val) = tx_queue.next_byte() {
+            sent_bytes.push(val);
+        }
+        sent_bytes
+    }
+
+    #[test]
+    fn send_msgs() {
+        let mut tx_queue = TxQueue::<32>::new();
+        tx_queue.send(&[1, 126, 3], 1).unwrap();
+        tx_queue.send_slices(&[&[4], &[126]], 2).unwrap();
+        assert_eq!(tx_queue.len(), 2 * SerMsg::encoded_len(3) - 1);
+
+        let mut expected = SerMsg::encode_vec(&[1, 126, 3], 1).unwrap();
+        expected.extend(SerMsg::encode_vec(&[4, 126], 2).unwrap());
+        assert_eq!(drain(&mut tx_queue), expected);
+        assert!(tx_queue.is_empty());
+    }
+
+    #[test]
+    fn full_queue() {
+        let mut tx_queue = TxQueue::<16>::new();
+        tx_queue.send(&[0; 8], 1).unwrap();
+        assert_eq!(tx_queue.free(), 2);
+        assert_eq!(tx_queue.send(&[], 2), Err(Error::QueueFull));
+        assert_eq!(
+            tx_queue.send(&[0; 255], 2),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+        // A message longer than the whole queue never fits
+        assert_eq!(
+            tx_queue.send(&[0; 20], 2),
+            Err(Error::BufferTooSmall {
+                len: 16,
+                needed: SerMsg::encoded_len(20)
+            })
+        );
+
+        // Sending the first bytes makes room for the next message, which wraps around
+        for _ in 0..6 {
+            tx_queue.next_byte().unwrap();
+        }
+        tx_queue.send(&[5, 6], 2).unwrap();
+        let mut expected = SerMsg::encode_vec(&[0; 8], 1).unwrap()[6..].to_vec();
+        expected.extend(SerMsg::encode_vec(&[5, 6], 2).unwrap());
+        assert_eq!(drain(&mut tx_queue), expected);
+    }
+
+    #[test]
+    fn send_with_crc() {
+        const CONFIG: FramingConfig = FramingConfig::new().with_start_byte(0x02);
+        let mut tx_queue = TxQueue::<32, Crc16>::with_crc(CONFIG, Crc16::new());
+        tx_queue.send(&[1, 2, 3], 1).unwrap();
+        let sent_bytes = drain(&mut tx_queue);
+
+        let mut ser_msg = SerMsg::with_crc(CONFIG, Crc16::new());
+        let mut bytes = &sent_bytes[..];
+        let frame = ser_msg.parse(&mut bytes).unwrap().unwrap();
+        assert_eq!(frame.payload, &[1, 2, 3]);
+    }
+}
+
+#[cfg(test)]
+mod message_tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn encode_and_decode() {
+        let msg = Message::new(3, &[1, 126, 3]).unwrap();
+        let send_msg = msg.encode();
+        assert_eq!(send_msg, SerMsg::encode_vec(&[1, 126, 3], 3).unwrap()[..]);
+        assert_eq!(msg.encode_vec(), &send_msg[..]);
+
+        let mut tx_buffer = [0; 16];
+        let msg_len = msg.encode_into(&mut tx_buffer).unwrap();
+        assert_eq!(&tx_buffer[..msg_len], &send_msg[..]);
+
+        assert_eq!(Message::try_from(&send_msg[..]), Ok(msg));
+        assert_eq!(
+            Message::try_from(&send_msg[1..]),
+            Err(Error::StartByte { found: 3 })
+        );
+        let frame = FrameRef {
+            id: 1,
+            payload: &[0; 255],
+        };
+        assert_eq!(
+            Message::try_from(frame),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+        assert_eq!(
+            Message::new(1, &[0; 255]),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+    }
+
+    #[test]
+    fn messages_in_map() {
+        let mut rcvd_bytes = SerMsg::encode_vec(&[1], 1).unwrap();
+        rcvd_bytes.extend(SerMsg::encode_vec(&[2], 2).unwrap());
+        rcvd_bytes.extend(SerMsg::encode_vec(&[1], 1).unwrap());
+
+        let mut ser_msg = SerMsg::new();
+        let mut counts = HashMap::new();
+        for msg in ser_msg.frames(&rcvd_bytes).filter_map(Result::ok) {
+            *counts.entry(msg).or_insert(0) += 1;
+        }
+        assert_eq!(counts[&Message::new(1, &[1]).unwrap()], 2);
+        assert_eq!(counts[&Message::new(2, &[2]).unwrap()], 1);
+    }
+
+    #[test]
+    fn message_through_channel() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        std::thread::spawn(move || {
+            let mut ser_msg = SerMsg::new();
+            let frame = ser_msg.parse(&mut &send_msg[..]).unwrap().unwrap();
+            sender.send(Message::try_from(frame).unwrap()).unwrap();
+        });
+        let msg = receiver.recv().unwrap();
+        assert_eq!(msg.as_frame_ref().payload, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn compare_parse_states() {
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        let mut ser_msg = SerMsg::new();
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg[..4]);
+        assert_eq!(state, ParseState::Continue);
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg[4..]);
+        let copied_state = state;
+        assert_eq!(copied_state, ParseState::DataReady);
+        assert_ne!(state, ParseState::CrcError);
+    }
+}
+
+#[cfg(test)]
+mod inspect_tests {
+    use super::*;
+
+    #[test]
+    fn valid_message() {
+        let send_msg = SerMsg::encode_vec(&[1, 126, 3, 126], 2).unwrap();
+        let report = inspect_frame(&send_msg);
+        assert!(report.is_ok());
+        assert_eq!(report.frame(), &send_msg[..]);
+        assert!(report.to_string().ends_with("no problems found"));
+    }
+
+    #[test]
+    fn every_problem_reported() {
+        let mut send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        send_msg[0] = 0;
+        send_msg[5] ^= 0xFF;
+        send_msg[8] = 0;
+        let report = inspect_frame(&send_msg);
+        match report.problems() {
+            [FrameProblem::StartByte { found: 0 }, FrameProblem::Crc { expected, received }, FrameProblem::StopByte { found: 0 }] =>
+            {
+                assert_eq!(*received, send_msg[7] as u32);
+                assert_ne!(expected, received);
+            }
+            problems => {
+                panic!("{:?}", problems);
+            }
+        }
+        assert!(report.to_string().ends_with("3 problems found"));
+    }
+
+    #[test]
+    fn broken_cobs_pointer() {
+        let mut send_msg = SerMsg::encode_vec(&[126, 2, 126], 1).unwrap();
+        // The pointer in the 1st payload byte now points behind the payload
+        send_msg[4] = 5;
+        let report = inspect_frame(&send_msg);
+        assert_eq!(
+            report.problems()[0],
+            FrameProblem::CobsPointer {
+                index: Some(0),
+                target: 5
+            }
+        );
+        assert!(report
+            .to_string()
+            .contains("Payload byte 0 (COBS pointer)        <- COBS pointer in payload byte 0"));
+    }
+
+    #[test]
+    fn unstuffed_start_bytes() {
+        let mut send_msg = SerMsg::encode_vec(&[1, 2, 3, 4], 1).unwrap();
+        send_msg[5] = 126;
+        send_msg[7] = 126;
+        let report = inspect_frame(&send_msg);
+        assert_eq!(
+            report.problems()[0],
+            FrameProblem::UnstuffedStartByte { index: 1, count: 2 }
+        );
+    }
+
+    #[test]
+    fn too_short() {
+        let report = inspect_frame(&[126, 1, 255]);
+        assert_eq!(report.problems(), &[FrameProblem::TooShort { len: 3 }]);
+        assert!(report.to_string().contains("11111111 COBS Overhead byte"));
+    }
+
+    #[test]
+    fn wrong_length() {
+        let mut send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        send_msg[3] = 4;
+        let report = inspect_frame(&send_msg);
+        assert_eq!(
+            report.problems(),
+            &[FrameProblem::FrameLength {
+                len: 9,
+                expected: 10
+            }]
+        );
+    }
+
+    #[test]
+    fn high_payload() {
+        let config = FramingConfig::new().with_max_payload(2);
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        let report = config.inspect_frame(&send_msg);
+        assert_eq!(report.problems(), &[FrameProblem::HighPayload { len: 3 }]);
+    }
+
+    #[test]
+    fn crc16_message() {
+        let send_msg = FramingConfig::DEFAULT
+            .encode_slices_vec_crc(&mut Crc16::new(), &[&[1, 2, 3]], 1)
+            .unwrap();
+        let report = FramingConfig::DEFAULT.inspect_frame_crc(&mut Crc16::new(), &send_msg);
+        assert!(report.is_ok());
+        assert!(report.to_string().contains("CRC byte 2 of 2"));
+    }
+}
+
+#[cfg(test)]
+mod introspection_tests {
+    use super::*;
+
+    #[test]
+    fn parser_states() {
+        let send_msg = SerMsg::encode_vec(&[1, 2], 1).unwrap();
+        let states = [
+            FindByte::Id,
+            FindByte::Overhead,
+            FindByte::PayloadLen,
+            FindByte::Payload,
+            FindByte::Payload,
+            FindByte::Crc,
+            FindByte::End,
+            FindByte::Start,
+        ];
+        let mut ser_msg = SerMsg::new();
+        assert_eq!(ser_msg.state(), FindByte::Start);
+        for (val, state) in send_msg.iter().zip(states.iter()) {
+            ser_msg.parse_read_bytes(&[*val]);
+            assert_eq!(ser_msg.state(), *state);
+        }
+    }
+
+    #[test]
+    fn in_progress_frame_bytes() {
+        let send_msg = SerMsg::encode_vec(&[126, 1, 126, 126, 2, 126], 1).unwrap();
+        for split_ind in 0..send_msg.len() {
+            let mut ser_msg = SerMsg::new();
+            ser_msg.parse_read_bytes(&send_msg[..split_ind]);
+            assert!(ser_msg
+                .frame_bytes()
+                .eq(send_msg[..split_ind].iter().copied()));
+            let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg[split_ind..]);
+            match state {
+                ParseState::DataReady => {
+                    assert!(ser_msg.frame_bytes().eq(send_msg.iter().copied()));
+                    assert_eq!(ser_msg.frame_bytes().len(), send_msg.len());
+                }
+                _ => {
+                    panic!()
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unstuffed_frame_bytes() {
+        let mut send_msg = SerMsg::encode_vec(&[126, 1, 126, 2], 1).unwrap();
+        // A start byte the overhead byte stuffing didn't replace
+        send_msg[5] = 126;
+        let mut ser_msg = SerMsg::new();
+        ser_msg.parse_read_bytes(&send_msg[..7]);
+        assert!(ser_msg.frame_bytes().eq(send_msg[..7].iter().copied()));
+    }
+
+    #[test]
+    fn crc_error_context() {
+        let mut send_msg = SerMsg::encode_vec(&[126, 2, 3], 1).unwrap();
+        send_msg[5] ^= 0xFF;
+        let mut ser_msg = SerMsg::new();
+        let mut bytes = &send_msg[..];
+        let (expected, received) = match ser_msg.parse(&mut bytes) {
+            Err(Error::Crc { expected, received }) => (expected, received),
+            _ => {
+                panic!()
+            }
+        };
+        assert_eq!(ser_msg.frame_crc(), Some((expected, received)));
+        assert_eq!(ser_msg.state(), FindByte::Start);
+        assert!(ser_msg.frame_bytes().eq(send_msg[..8].iter().copied()));
+        assert_eq!(
+            inspect_frame(&send_msg).problems(),
+            &[FrameProblem::Crc { expected, received }]
+        );
+
+        // The bytes are kept until the next message starts
+        ser_msg.parse_read_bytes(&[0, 1]);
+        assert!(ser_msg.frame_bytes().eq(send_msg[..8].iter().copied()));
+        ser_msg.parse_read_bytes(&[126]);
+        assert!(ser_msg.frame_bytes().eq([126].iter().copied()));
+        assert_eq!(ser_msg.frame_crc(), None);
+    }
+
+    #[test]
+    fn stop_byte_error_context() {
+        let mut send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        send_msg[8] = 0;
+        let mut ser_msg = SerMsg::new();
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&send_msg);
+        match state {
+            ParseState::StopByteError => {
+                assert!(ser_msg.frame_bytes().eq(send_msg.iter().copied()));
+                let (expected, received) = ser_msg.frame_crc().unwrap();
+                assert_eq!(expected, received);
+            }
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn frame_offsets() {
+        let mut rcvd_bytes = vec![0, 1, 2];
+        rcvd_bytes.extend(SerMsg::encode_vec(&[1, 2, 3], 1).unwrap());
+        rcvd_bytes.extend(SerMsg::encode_vec(&[4], 2).unwrap());
+
+        let mut ser_msg = SerMsg::new();
+        let (_state, parsed_bytes) = ser_msg.parse_read_bytes(&rcvd_bytes[..5]);
+        assert_eq!(parsed_bytes, 5);
+        let mut bytes = &rcvd_bytes[5..];
+        assert!(ser_msg.parse(&mut bytes).unwrap().is_some());
+        assert_eq!(ser_msg.frame_offset(), 3);
+        assert!(ser_msg.parse(&mut bytes).unwrap().is_some());
+        assert_eq!(ser_msg.frame_offset(), 12);
+    }
+
+    #[test]
+    fn resync_frame_offset() {
+        // A start byte caused by line noise right before the message
+        let mut rcvd_bytes = vec![1, 126];
+        rcvd_bytes.extend(SerMsg::encode_vec(&[1, 2, 3], 1).unwrap());
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_resync(true);
+        let (state, parsed_bytes) = ser_msg.parse_read_bytes(&rcvd_bytes);
+        match state {
+            ParseState::HighPayloadError => {
+                assert_eq!(ser_msg.frame_offset(), 1);
+                assert!(ser_msg.frame_bytes().eq(rcvd_bytes[1..5].iter().copied()));
+            }
+            _ => {
+                panic!()
+            }
+        }
+        let (state, _parsed_bytes) = ser_msg.parse_read_bytes(&rcvd_bytes[parsed_bytes..]);
+        match state {
+            ParseState::DataReady => {
+                assert_eq!(ser_msg.frame_offset(), 2);
+                assert!(ser_msg.frame_bytes().eq(rcvd_bytes[2..].iter().copied()));
+            }
+            _ => {
+                panic!()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::*;
+
+    #[test]
+    fn count_frames_and_errors() {
+        let mut rcvd_bytes = vec![1, 2, 3];
+        rcvd_bytes.extend(SerMsg::encode_vec(&[1, 2, 3], 1).unwrap());
+        let mut crc_error_msg = SerMsg::encode_vec(&[1, 2, 3], 2).unwrap();
+        crc_error_msg[5] ^= 0xFF;
+        rcvd_bytes.extend(&crc_error_msg);
+        let mut stop_error_msg = SerMsg::encode_vec(&[1], 2).unwrap();
+        stop_error_msg[6] = 0;
+        rcvd_bytes.extend(&stop_error_msg);
+        rcvd_bytes.extend(SerMsg::encode_vec(&[4], 1).unwrap());
+        rcvd_bytes.extend(SerMsg::encode_vec(&[], 3).unwrap());
+
+        let mut ser_msg = SerMsg::new();
+        let mut stats = LinkStats::new();
+        let mut bytes = &rcvd_bytes[..];
+        while !bytes.is_empty() {
+            let _ = ser_msg.parse_stats(&mut bytes, &mut stats);
+        }
+        assert_eq!(stats.frames_ok, 3);
+        assert_eq!(stats.crc_errors, 1);
+        assert_eq!(stats.stop_byte_errors, 1);
+        assert_eq!(stats.frame_errors(), 2);
+        assert_eq!(stats.frames_with_id(1), 2);
+        assert_eq!(stats.frames_with_id(2), 0);
+        assert_eq!(stats.frames_with_id(3), 1);
+        assert_eq!(stats.bytes_received, rcvd_bytes.len() as u64);
+        // The stop byte after the CRC error is discarded as well
+        assert_eq!(stats.discarded_bytes, 4);
+        assert_eq!(stats.error_rate(), 0.4);
+    }
+
+    #[test]
+    fn error_rate_window() {
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        let mut crc_error_msg = send_msg.clone();
+        crc_error_msg[5] ^= 0xFF;
+
+        let mut ser_msg = SerMsg::new();
+        let mut stats = LinkStats::new();
+        assert_eq!(stats.error_rate(), 0.0);
+        for _ in 0..LinkStats::ERROR_RATE_WINDOW {
+            ser_msg.parse_read_bytes_stats(&crc_error_msg, &mut stats);
+        }
+        assert_eq!(stats.error_rate(), 1.0);
+        for _ in 0..LinkStats::ERROR_RATE_WINDOW / 4 {
+            ser_msg.parse_read_bytes_stats(&send_msg, &mut stats);
+        }
+        assert_eq!(stats.error_rate(), 0.75);
+        assert_eq!(stats.crc_errors, LinkStats::ERROR_RATE_WINDOW as u32);
+    }
+
+    #[test]
+    fn snapshot_and_reset() {
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        let mut ser_msg = SerMsg::new();
+        let mut stats = LinkStats::new();
+        stats.record_sent(&send_msg);
+        ser_msg.parse_read_bytes_stats(&send_msg, &mut stats);
+
+        let snapshot = stats.clone();
+        stats.reset();
+        assert_eq!(stats, LinkStats::default());
+        assert_eq!(snapshot.frames_sent, 1);
+        assert_eq!(snapshot.bytes_sent, send_msg.len() as u64);
+        assert_eq!(snapshot.frames_ok, 1);
+        assert_eq!(snapshot.frames_with_id(1), 1);
+    }
+
+    #[test]
+    fn resync_bytes_counted_once() {
+        // A start byte caused by line noise right before the message
+        let mut rcvd_bytes = vec![126];
+        rcvd_bytes.extend(SerMsg::encode_vec(&[1, 2, 3], 1).unwrap());
+
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_resync(true);
+        let mut stats = LinkStats::new();
+        let mut bytes = &rcvd_bytes[..];
+        while !bytes.is_empty() || ser_msg.has_resync_bytes() {
+            let _ = ser_msg.parse_stats(&mut bytes, &mut stats);
+        }
+        assert_eq!(stats.high_payload_errors, 1);
+        assert_eq!(stats.frames_ok, 1);
+        assert_eq!(stats.bytes_received, rcvd_bytes.len() as u64);
+        assert_eq!(stats.discarded_bytes, 0);
+    }
+
+    #[test]
+    fn count_stale_messages() {
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        let clock = TickClock::new();
+        let mut ser_msg = SerMsg::new();
+        ser_msg.set_timeout(Some(50));
+        let mut stats = LinkStats::new();
+
+        // The rest of the cut off message arrives too late
+        let (parse_state, _) =
+            ser_msg.parse_read_bytes_timed_stats(&send_msg[..4], &clock, &mut stats);
+        match parse_state {
+            ParseState::Continue => {}
+            _ => {
+                panic!()
+            }
+        }
+        clock.advance(50);
+        let mut bytes = &send_msg[..];
+        while !bytes.is_empty() {
+            let _ = ser_msg.parse_timed_stats(&mut bytes, &clock, &mut stats);
+        }
+        assert_eq!(stats.stale_errors, 1);
+        assert_eq!(stats.frames_ok, 1);
+        assert_eq!(stats.frame_errors(), 1);
+        assert_eq!(stats.error_rate(), 0.5);
+    }
+
+    #[test]
+    fn count_sent_messages() {
+        let mut stats = LinkStats::new();
+        let mut tx_buffer = [0; 16];
+        let msg_len = SerMsg::encode_into_stats(&[1, 2, 3], 1, &mut tx_buffer, &mut stats).unwrap();
+        let mut send_buf = Vec::new();
+        SerMsg::encode_extend_stats(&[4], 2, &mut send_buf, &mut stats).unwrap();
+        assert_eq!(
+            SerMsg::encode_extend_stats(&[0; 255], 3, &mut send_buf, &mut stats),
+            Err(Error::PayloadTooLarge { len: 255 })
+        );
+        assert_eq!(stats.frames_sent, 2);
+        assert_eq!(stats.bytes_sent, (msg_len + send_buf.len()) as u64);
+    }
+}
+
+#[cfg(feature = "std")]
+mod transfer_tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::io::{self, Read, Write};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    // One end of an in-memory duplex stream, reading returns WouldBlock like a
+    // non-blocking serial port if no bytes are available
+    struct DuplexEnd {
+        rx: Arc<Mutex<VecDeque<u8>>>,
+        tx: Arc<Mutex<VecDeque<u8>>>,
+    }
+
+    impl Read for DuplexEnd {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut rx = self.rx.lock().unwrap();
+            if rx.is_empty() {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            let len = buf.len().min(rx.len());
+            for (dest, val) in buf.iter_mut().zip(rx.drain(..len)) {
+                *dest = val;
+            }
+            Ok(len)
+        }
+    }
+
+    impl Write for DuplexEnd {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.tx.lock().unwrap().extend(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn duplex() -> (DuplexEnd, DuplexEnd) {
+        let a_to_b = Arc::new(Mutex::new(VecDeque::new()));
+        let b_to_a = Arc::new(Mutex::new(VecDeque::new()));
+        (
+            DuplexEnd {
+                rx: b_to_a.clone(),
+                tx: a_to_b.clone(),
+            },
+            DuplexEnd {
+                rx: a_to_b,
+                tx: b_to_a,
+            },
+        )
+    }
+
+    const TIMEOUT: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn send_and_receive() {
+        let (a, b) = duplex();
+        let mut transfer_a = Transfer::new(a);
+        let mut transfer_b = Transfer::new(b);
+
+        transfer_a.send(&[1, 126, 3], 1).unwrap();
+        transfer_a.send(&[4], 2).unwrap();
+        assert_eq!(
+            transfer_b.receive(TIMEOUT).unwrap(),
+            Message::new(1, &[1, 126, 3]).unwrap()
+        );
+        assert_eq!(
+            transfer_b.receive(TIMEOUT).unwrap(),
+            Message::new(2, &[4]).unwrap()
+        );
+
+        transfer_b.send(&[5, 6], 3).unwrap();
+        assert_eq!(
+            transfer_a.receive(TIMEOUT).unwrap(),
+            Message::new(3, &[5, 6]).unwrap()
+        );
+    }
+
+    #[test]
+    fn receive_timeout() {
+        let (a, _b) = duplex();
+        let mut transfer = Transfer::new(a);
+        let e = transfer.receive(Duration::from_millis(10)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn available_across_reads() {
+        let (a, mut b) = duplex();
+        let mut transfer = Transfer::new(a);
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 7).unwrap();
+
+        assert!(!transfer.available().unwrap());
+        b.write_all(&send_msg[..4]).unwrap();
+        assert!(!transfer.available().unwrap());
+        b.write_all(&send_msg[4..]).unwrap();
+        assert!(transfer.available().unwrap());
+        assert_eq!(transfer.packet_id(), 7);
+        assert_eq!(transfer.payload(), &[1, 2, 3]);
+        assert!(!transfer.available().unwrap());
+    }
+
+    #[test]
+    fn corrupted_message() {
+        let (a, mut b) = duplex();
+        let mut transfer = Transfer::new(a);
+        let mut send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        send_msg[5] ^= 0xFF;
+        b.write_all(&send_msg).unwrap();
+        b.write_all(&SerMsg::encode_vec(&[4], 2).unwrap()).unwrap();
+
+        // The corrupted message is skipped and the next one received before the timeout
+        assert_eq!(
+            transfer.receive(TIMEOUT).unwrap(),
+            Message::new(2, &[4]).unwrap()
+        );
+        assert_eq!(transfer.skipped_frames(), 1);
+
+        b.write_all(&send_msg).unwrap();
+        let e = loop {
+            match transfer.available() {
+                Ok(false) => (),
+                result => break result.unwrap_err(),
+            }
+        };
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        let e = e.into_inner().unwrap().downcast::<Error>().unwrap();
+        assert!(matches!(*e, Error::Crc { .. }));
+    }
+
+    #[test]
+    fn payload_too_large() {
+        let (a, b) = duplex();
+        let mut transfer = Transfer::new(a);
+        let e = transfer.send(&[0; 255], 1).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert!(b.rx.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn end_of_stream() {
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        let mut transfer = Transfer::new(io::Cursor::new(send_msg));
+        assert_eq!(transfer.receive(TIMEOUT).unwrap().payload, [1, 2, 3]);
+        let e = transfer.receive(Duration::from_millis(10)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[cfg(feature = "zerocopy")]
+    #[test]
+    fn send_and_receive_objects() {
+        let (a, b) = duplex();
+        let mut transfer_a = Transfer::new(a);
+        let mut transfer_b = Transfer::new(b);
+
+        transfer_a.send_obj(&[1.5_f32, 2.5], 1).unwrap();
+        while !transfer_b.available().unwrap() {}
+        assert_eq!(transfer_b.rx_obj::<f32>(4), Ok(2.5));
+        assert!(transfer_b.rx_obj::<f32>(6).is_err());
+    }
+}
+
+#[cfg(feature = "std")]
+mod link_tests {
+    use super::*;
+    use std::io::{self, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc::RecvTimeoutError;
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    // Returns a link and the stream of the device on the other end of the port
+    fn link() -> (LinkHandle, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (device, _) = listener.accept().unwrap();
+        port.set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        let link = LinkHandle::spawn(port.try_clone().unwrap(), port).unwrap();
+        (link, device)
+    }
+
+    #[test]
+    fn subscriptions_by_id() {
+        let (link, mut device) = link();
+        let id_1 = link.subscribe(1);
+        let id_2 = link.subscribe(2);
+        let id_1_to_2 = link.subscribe_range(1..=2);
+
+        for (data, id) in [(&[1][..], 1), (&[2], 2), (&[3], 3), (&[4], 1)] {
+            device
+                .write_all(&SerMsg::encode_vec(data, id).unwrap())
+                .unwrap();
+        }
+        assert_eq!(id_1.recv_timeout(TIMEOUT).unwrap().payload, [1]);
+        assert_eq!(id_1.recv_timeout(TIMEOUT).unwrap().payload, [4]);
+        assert_eq!(id_2.recv_timeout(TIMEOUT).unwrap().payload, [2]);
+        let ids: Vec<u8> = (0..3)
+            .map(|_| id_1_to_2.recv_timeout(TIMEOUT).unwrap().id)
+            .collect();
+        assert_eq!(ids, vec![1, 2, 1]);
+        assert!(id_2.try_recv().is_err());
+        assert_eq!(link.stats().frames_ok, 4);
+        link.shutdown().unwrap();
+    }
+
+    #[test]
+    fn concurrent_senders() {
+        let (link, device) = link();
+        let threads: Vec<_> = (0..4)
+            .map(|id| {
+                let sender = link.sender();
+                std::thread::spawn(move || {
+                    for val in 0..50 {
+                        sender.send(&[val; 100], id).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let mut rcvd_bytes = vec![0; 200 * SerMsg::encoded_len(100)];
+        io::Read::read_exact(&mut &device, &mut rcvd_bytes).unwrap();
+        let mut ser_msg = SerMsg::new();
+        let mut counts = [0; 4];
+        for msg in ser_msg.frames(&rcvd_bytes) {
+            counts[msg.unwrap().id as usize] += 1;
+        }
+        assert_eq!(counts, [50; 4]);
+        assert_eq!(link.stats().frames_sent, 200);
+        link.shutdown().unwrap();
+    }
+
+    #[test]
+    fn port_closed() {
+        let (link, device) = link();
+        let all_ids = link.subscribe_range(0..=255);
+        drop(device);
+
+        // The subscribers are disconnected when the reader thread stops
+        assert_eq!(
+            all_ids.recv_timeout(TIMEOUT),
+            Err(RecvTimeoutError::Disconnected)
+        );
+        assert!(link.subscribe(1).recv().is_err());
+        let e = link.shutdown().unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn clean_shutdown() {
+        let (link, _device) = link();
+        let id_1 = link.subscribe(1);
+        assert!(link.is_running());
+        link.shutdown().unwrap();
+        assert!(id_1.recv().is_err());
+    }
+
+    #[test]
+    fn dropped_receiver() {
+        let (link, mut device) = link();
+        drop(link.subscribe(1));
+        let id_1 = link.subscribe(1);
+        device
+            .write_all(&SerMsg::encode_vec(&[1], 1).unwrap())
+            .unwrap();
+        assert_eq!(id_1.recv_timeout(TIMEOUT).unwrap().payload, [1]);
+        link.shutdown().unwrap();
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod codec_tests {
+    use super::*;
+    use bytes::{Bytes, BytesMut};
+    use futures::{SinkExt, StreamExt};
+    use std::io;
+    use tokio::io::AsyncWriteExt;
+    use tokio_util::codec::{Decoder, Encoder, Framed, FramedRead};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn send_receive() {
+        block_on(async {
+            let (port, device) = tokio::io::duplex(16);
+            let mut port = Framed::new(port, SerMsgCodec::new());
+            let mut device = Framed::new(device, SerMsgCodec::new());
+            let payload: Vec<u8> = (0..254).map(|val| val as u8).collect();
+
+            let sending = async {
+                port.send((1, Bytes::from(payload.clone()))).await.unwrap();
+                port.send((2, Bytes::from_static(&[0x7E, 0x81])))
+                    .await
+                    .unwrap();
+                port.send((3, Bytes::new())).await.unwrap();
+            };
+            let receiving = async {
+                let mut msgs = Vec::new();
+                for _ in 0..3 {
+                    msgs.push(device.next().await.unwrap().unwrap());
+                }
+                msgs
+            };
+            let ((), msgs) = tokio::join!(sending, receiving);
+            assert_eq!(msgs[0], Message::new(1, &payload).unwrap());
+            assert_eq!(msgs[1], Message::new(2, &[0x7E, 0x81]).unwrap());
+            assert_eq!(msgs[2], Message::new(3, &[]).unwrap());
+        });
+    }
+
+    #[test]
+    fn decode_split() {
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        let mut codec = SerMsgCodec::new();
+        let mut src = BytesMut::from(&[0, 0][..]);
+        src.extend_from_slice(&send_msg[..5]);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        // Bytes in front of the start byte are dropped right away
+        assert_eq!(&src[..], &send_msg[..5]);
+        src.extend_from_slice(&send_msg[5..]);
+        let msg = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(msg, Message::new(1, &[1, 2, 3]).unwrap());
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn skip_errors() {
+        let mut bad_crc = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        bad_crc[7] ^= 0xFF;
+        let mut src = BytesMut::from(&bad_crc[..]);
+        src.extend_from_slice(&SerMsg::encode_vec(&[4], 2).unwrap());
+
+        let mut codec = SerMsgCodec::new();
+        assert_eq!(codec.on_error(), OnFrameError::Skip);
+        let msg = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(msg, Message::new(2, &[4]).unwrap());
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn resync_inside_failed_message() {
+        // The payload length of the false start byte exceeds the bytes received, the message
+        // behind it is still found once the stream ends
+        let mut rcvd_bytes = vec![0x7E, 0, 0, 50];
+        rcvd_bytes.extend(SerMsg::encode_vec(&[1, 2], 3).unwrap());
+        let reader = FramedRead::new(&rcvd_bytes[..], SerMsgCodec::new());
+        let msgs: Vec<Message> = block_on(reader.map(Result::unwrap).collect());
+        assert_eq!(msgs, vec![Message::new(3, &[1, 2]).unwrap()]);
+    }
+
+    #[test]
+    fn yield_errors() {
+        let mut bad_cobs = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        bad_cobs[2] = 1;
+        let mut codec = SerMsgCodec::new();
+        codec.set_on_error(OnFrameError::Yield);
+        let mut src = BytesMut::from(&bad_cobs[..]);
+        src.extend_from_slice(&SerMsg::encode_vec(&[4], 2).unwrap());
+
+        let e = codec.decode(&mut src).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(e.into_inner().unwrap().downcast_ref(), Some(&Error::Cobs));
+        // Decoding continues behind the start byte of the failed message
+        let msg = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(msg, Message::new(2, &[4]).unwrap());
+    }
+
+    #[test]
+    fn yield_high_payload() {
+        let mut codec = SerMsgCodec::with_config(FramingConfig::DEFAULT.with_max_payload(2));
+        codec.set_on_error(OnFrameError::Yield);
+        let mut src = BytesMut::from(&SerMsg::encode_vec(&[1, 2, 3], 1).unwrap()[..]);
+        let e = codec.decode(&mut src).unwrap_err();
+        assert_eq!(
+            e.into_inner().unwrap().downcast_ref(),
+            Some(&Error::HighPayload { len: 3 })
+        );
+    }
+
+    #[test]
+    fn yield_truncated_message() {
+        block_on(async {
+            let (mut port, device) = tokio::io::duplex(64);
+            let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+            port.write_all(&send_msg[..6]).await.unwrap();
+            drop(port);
+
+            let mut codec = SerMsgCodec::new();
+            codec.set_on_error(OnFrameError::Yield);
+            let mut device = FramedRead::new(device, codec);
+            let e = device.next().await.unwrap().unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+            assert!(device.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn crc16() {
+        let mut codec = SerMsgCodec::with_crc(FramingConfig::DEFAULT, Crc16::new());
+        let mut dst = BytesMut::new();
+        codec
+            .encode((5, Bytes::from_static(&[1, 2, 3])), &mut dst)
+            .unwrap();
+        assert_eq!(dst.len(), SerMsg::encoded_len_crc::<Crc16>(3));
+        let msg = codec.decode(&mut dst).unwrap().unwrap();
+        assert_eq!(msg, Message::new(5, &[1, 2, 3]).unwrap());
+    }
+
+    #[test]
+    fn encode_too_large() {
+        let mut codec = SerMsgCodec::new();
+        let mut dst = BytesMut::new();
+        let e = codec
+            .encode((1, Bytes::from(vec![0; 255])), &mut dst)
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert!(dst.is_empty());
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+mod embedded_tests {
+    use super::*;
+    use embedded_hal_nb::{nb, serial};
+    use std::collections::VecDeque;
+
+    #[derive(Debug, PartialEq)]
+    struct MockError;
+
+    impl embedded_io::Error for MockError {
+        fn kind(&self) -> embedded_io::ErrorKind {
+            embedded_io::ErrorKind::Other
+        }
+    }
+
+    impl serial::Error for MockError {
+        fn kind(&self) -> serial::ErrorKind {
+            serial::ErrorKind::Other
+        }
+    }
+
+    // A UART driver returning at most `chunk_len` bytes per read, every other
+    // byte written through the embedded-hal-nb traits would block
+    #[derive(Default)]
+    struct MockUart {
+        rx: VecDeque<u8>,
+        tx: Vec<u8>,
+        chunk_len: usize,
+        would_block: bool,
+        fail: bool,
+    }
+
+    impl MockUart {
+        fn new(rx: &[u8], chunk_len: usize) -> MockUart {
+            MockUart {
+                rx: rx.iter().copied().collect(),
+                chunk_len,
+                ..MockUart::default()
+            }
+        }
+    }
+
+    impl embedded_io::ErrorType for MockUart {
+        type Error = MockError;
+    }
+
+    impl embedded_io::Read for MockUart {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, MockError> {
+            if self.fail {
+                return Err(MockError);
+            }
+            let len = buf.len().min(self.chunk_len).min(self.rx.len());
+            for (dst, val) in buf.iter_mut().zip(self.rx.drain(..len)) {
+                *dst = val;
+            }
+            Ok(len)
+        }
+    }
+
+    impl embedded_io::ReadReady for MockUart {
+        fn read_ready(&mut self) -> Result<bool, MockError> {
+            Ok(!self.rx.is_empty())
+        }
+    }
+
+    impl embedded_io::Write for MockUart {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, MockError> {
+            if self.fail {
+                return Err(MockError);
+            }
+            let len = buf.len().min(self.chunk_len);
+            self.tx.extend(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> Result<(), MockError> {
+            Ok(())
+        }
+    }
+
+    impl serial::ErrorType for MockUart {
+        type Error = MockError;
+    }
+
+    impl serial::Read for MockUart {
+        fn read(&mut self) -> nb::Result<u8, MockError> {
+            if self.fail {
+                return Err(nb::Error::Other(MockError));
+            }
+            self.rx.pop_front().ok_or(nb::Error::WouldBlock)
+        }
+    }
+
+    impl serial::Write for MockUart {
+        fn write(&mut self, word: u8) -> nb::Result<(), MockError> {
+            if self.fail {
+                return Err(nb::Error::Other(MockError));
+            }
+            self.would_block = !self.would_block;
+            if self.would_block {
+                return Err(nb::Error::WouldBlock);
+            }
+            self.tx.push(word);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> nb::Result<(), MockError> {
+            Ok(())
+        }
+    }
+
+    fn two_msgs() -> Vec<u8> {
+        let mut rcvd_bytes = vec![0, 1];
+        rcvd_bytes.extend(SerMsg::encode_vec(&[1, 126, 3], 1).unwrap());
+        rcvd_bytes.extend(SerMsg::encode_vec(&[4, 5], 2).unwrap());
+        rcvd_bytes
+    }
+
+    #[test]
+    fn poll_chunks() {
+        let mut transfer = EmbeddedTransfer::new(MockUart::new(&two_msgs(), 5));
+        assert!(transfer.poll().unwrap());
+        assert_eq!(
+            (transfer.packet_id(), transfer.payload()),
+            (1, &[1, 126, 3][..])
+        );
+        // The second message starts in the chunk of the first one
+        assert!(transfer.poll().unwrap());
+        assert_eq!((transfer.packet_id(), transfer.payload()), (2, &[4, 5][..]));
+        assert!(!transfer.poll().unwrap());
+    }
+
+    #[test]
+    fn receive_eof() {
+        let mut transfer = EmbeddedTransfer::new(MockUart::new(&two_msgs(), 32));
+        assert_eq!(
+            transfer.receive().unwrap(),
+            Message::new(1, &[1, 126, 3]).unwrap()
+        );
+        assert_eq!(
+            transfer.receive().unwrap(),
+            Message::new(2, &[4, 5]).unwrap()
+        );
+        assert_eq!(transfer.receive(), Err(TransferError::Eof));
+    }
+
+    #[test]
+    fn poll_crc_error() {
+        let mut rcvd_bytes = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        rcvd_bytes[7] ^= 0xFF;
+        let mut transfer = EmbeddedTransfer::new(MockUart::new(&rcvd_bytes, 32));
+        match transfer.poll() {
+            Err(TransferError::Message(Error::Crc { .. })) => {}
+            _ => {
+                panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn send_chunks() {
+        let send_data: Vec<u8> = (0..254).map(|val| val as u8).collect();
+        let mut transfer = EmbeddedTransfer::new(MockUart::new(&[], 7));
+        transfer.send(&send_data, 3).unwrap();
+        assert_eq!(
+            transfer.port().tx,
+            SerMsg::encode_vec(&send_data, 3).unwrap()
+        );
+        assert_eq!(
+            transfer.send(&[0; 255], 3),
+            Err(TransferError::Message(Error::PayloadTooLarge { len: 255 }))
+        );
+    }
+
+    #[test]
+    fn port_errors() {
+        let mut transfer = EmbeddedTransfer::new(MockUart::new(&two_msgs(), 32));
+        transfer.port_mut().fail = true;
+        assert_eq!(transfer.receive(), Err(TransferError::Port(MockError)));
+        assert_eq!(transfer.send(&[1], 1), Err(TransferError::Port(MockError)));
+
+        let mut transfer = NbTransfer::new(MockUart::new(&two_msgs(), 32));
+        transfer.port_mut().fail = true;
+        assert_eq!(transfer.poll(), Err(TransferError::Port(MockError)));
+        assert_eq!(transfer.send(&[1], 1), Err(TransferError::Port(MockError)));
+    }
+
+    #[test]
+    fn nb_poll() {
+        let mut transfer = NbTransfer::new(MockUart::new(&two_msgs(), 1));
+        assert!(transfer.poll().unwrap());
+        assert_eq!(
+            (transfer.packet_id(), transfer.payload()),
+            (1, &[1, 126, 3][..])
+        );
+        assert!(transfer.poll().unwrap());
+        assert_eq!((transfer.packet_id(), transfer.payload()), (2, &[4, 5][..]));
+        assert!(!transfer.poll().unwrap());
+        assert!(transfer.port().rx.is_empty());
+    }
+
+    #[test]
+    fn nb_poll_resync() {
+        let mut rcvd_bytes = vec![126];
+        rcvd_bytes.extend(SerMsg::encode_vec(&[1, 2, 3], 1).unwrap());
+        let mut transfer = NbTransfer::new(MockUart::new(&rcvd_bytes, 1));
+        transfer.parser_mut().set_resync(true);
+        match transfer.poll() {
+            Err(TransferError::Message(Error::HighPayload { .. })) => {}
+            _ => {
+                panic!()
+            }
+        }
+        assert!(transfer.poll().unwrap());
+        assert_eq!(
+            (transfer.packet_id(), transfer.payload()),
+            (1, &[1, 2, 3][..])
+        );
+        assert!(!transfer.poll().unwrap());
+    }
+
+    #[test]
+    fn nb_send() {
+        let mut transfer = NbTransfer::new(MockUart::new(&[], 1));
+        transfer.send(&[1, 126, 3], 1).unwrap();
+        assert_eq!(
+            transfer.into_inner().tx,
+            SerMsg::encode_vec(&[1, 126, 3], 1).unwrap()
+        );
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+mod async_transfer_tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::FutureExt;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::future::poll_fn;
+    use std::rc::Rc;
+    use std::task::{Poll, Waker};
+
+    #[derive(Default)]
+    struct Pipe {
+        bytes: VecDeque<u8>,
+        waker: Option<Waker>,
+        closed: bool,
+    }
+
+    // One end of an in-memory pipe, reading waits until bytes are available
+    struct PipeEnd {
+        rx: Rc<RefCell<Pipe>>,
+        tx: Rc<RefCell<Pipe>>,
+    }
+
+    fn pipe() -> (PipeEnd, PipeEnd) {
+        let a = Rc::new(RefCell::new(Pipe::default()));
+        let b = Rc::new(RefCell::new(Pipe::default()));
+        (
+            PipeEnd {
+                rx: a.clone(),
+                tx: b.clone(),
+            },
+            PipeEnd { rx: b, tx: a },
+        )
+    }
+
+    impl embedded_io_async::ErrorType for PipeEnd {
+        type Error = core::convert::Infallible;
+    }
+
+    impl embedded_io_async::Read for PipeEnd {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            poll_fn(|cx| {
+                let mut rx = self.rx.borrow_mut();
+                if rx.bytes.is_empty() && !rx.closed {
+                    rx.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+                let len = buf.len().min(rx.bytes.len());
+                for (dst, val) in buf.iter_mut().zip(rx.bytes.drain(..len)) {
+                    *dst = val;
+                }
+                Poll::Ready(Ok(len))
+            })
+            .await
+        }
+    }
+
+    impl embedded_io_async::Write for PipeEnd {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            let mut tx = self.tx.borrow_mut();
+            tx.bytes.extend(buf);
+            if let Some(waker) = tx.waker.take() {
+                waker.wake();
+            }
+            Ok(buf.len())
+        }
+    }
+
+    impl Drop for PipeEnd {
+        fn drop(&mut self) {
+            let mut tx = self.tx.borrow_mut();
+            tx.closed = true;
+            if let Some(waker) = tx.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    #[test]
+    fn send_receive() {
+        let (port, device) = pipe();
+        let mut port = AsyncTransfer::new(port);
+        let mut device = AsyncTransfer::new(device);
+        let send_data: Vec<u8> = (0..254).map(|val| val as u8).collect();
+
+        let (sent, rcvd) = block_on(async {
+            futures::join!(
+                async {
+                    port.send(&send_data, 1).await?;
+                    port.send(&[126], 2).await
+                },
+                async {
+                    Ok::<_, TransferError<_>>((device.receive().await?, device.receive().await?))
+                }
+            )
+        });
+        sent.unwrap();
+        assert_eq!(
+            rcvd.unwrap(),
+            (
+                Message::new(1, &send_data).unwrap(),
+                Message::new(2, &[126]).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn cancelled_receive() {
+        let (port, device) = pipe();
+        let mut device = AsyncTransfer::new(device);
+        let send_msg = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+
+        port.tx.borrow_mut().bytes.extend(&send_msg[..5]);
+        // Polls the future once and drops it, like a select! completing another branch
+        assert!(device.receive().now_or_never().is_none());
+        assert!(port.tx.borrow().bytes.is_empty());
+
+        port.tx.borrow_mut().bytes.extend(&send_msg[5..]);
+        let msg = device.receive().now_or_never().unwrap().unwrap();
+        assert_eq!(msg, Message::new(1, &[1, 2, 3]).unwrap());
+    }
+
+    #[test]
+    fn receive_errors() {
+        let (port, device) = pipe();
+        let mut device = AsyncTransfer::new(device);
+        let mut rcvd_bytes = SerMsg::encode_vec(&[1, 2, 3], 1).unwrap();
+        rcvd_bytes[7] ^= 0xFF;
+        rcvd_bytes.extend(SerMsg::encode_vec(&[4], 2).unwrap());
+        port.tx.borrow_mut().bytes.extend(&rcvd_bytes);
+        drop(port);
+
+        match block_on(device.receive()) {
+            Err(TransferError::Message(Error::Crc { .. })) => {}
+            _ => {
+                panic!()
+            }
+        }
+        assert_eq!(
+            block_on(device.receive()),
+            Ok(Message::new(2, &[4]).unwrap())
+        );
+        assert_eq!(block_on(device.receive()), Err(TransferError::Eof));
+    }
+
+    #[test]
+    fn send_too_large() {
+        let mut tx_buffer = [0; 300];
+        let mut transfer = AsyncTransfer::new(&mut tx_buffer[..]);
+        assert_eq!(
+            block_on(transfer.send(&[0; 255], 1)),
+            Err(TransferError::Message(Error::PayloadTooLarge { len: 255 }))
+        );
+    }
+}